@@ -0,0 +1,298 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use color_eyre::eyre::eyre;
+use git2::Repository;
+
+use crate::editors::{rebase::todo::comment_string, shell::quote};
+
+#[derive(clap::Args)]
+pub struct RebaseArgs {
+    /// Edit the todo list with glitt before the rebase starts
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Continue a stopped rebase and stay attached to it
+    #[arg(long = "continue", conflicts_with_all = ["abort", "upstream"])]
+    continue_rebase: bool,
+
+    /// Abort the rebase in progress
+    #[arg(long, conflicts_with = "upstream")]
+    abort: bool,
+
+    /// The upstream to rebase onto, defaults to the branch's upstream
+    upstream: Option<String>,
+
+    /// Extra arguments passed through to `git rebase`
+    #[arg(last = true)]
+    args: Vec<String>,
+}
+
+/// Why git handed control back in the middle of a rebase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Conflict,
+    Edit,
+    Break,
+    ExecFailed,
+    Other,
+}
+
+impl StopReason {
+    /// Classify a stop from the last line of `rebase-merge/done`. Conflicts
+    /// take priority, since any picking command can stop on them.
+    pub fn classify(last_done: Option<&str>, has_conflicts: bool) -> Self {
+        if has_conflicts {
+            return StopReason::Conflict;
+        }
+
+        let action = last_done
+            .and_then(|line| line.split_whitespace().next())
+            .unwrap_or_default();
+        match action {
+            "edit" | "e" => StopReason::Edit,
+            "break" | "b" => StopReason::Break,
+            "exec" | "x" => StopReason::ExecFailed,
+            _ => StopReason::Other,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            StopReason::Conflict => "conflict",
+            StopReason::Edit => "edit",
+            StopReason::Break => "break",
+            StopReason::ExecFailed => "failed exec",
+            StopReason::Other => "stop",
+        }
+    }
+}
+
+/// The state git keeps in `.git/rebase-merge` or `.git/rebase-apply` while a
+/// rebase is stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RebaseProgress {
+    pub step: usize,
+    pub total: usize,
+    pub last_done: Option<String>,
+}
+
+impl RebaseProgress {
+    pub fn parse(msgnum: &str, end: &str, done: &str, comment: &str) -> Self {
+        let last_done = done
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty() && !line.starts_with(comment))
+            .map(str::to_string);
+
+        RebaseProgress {
+            step: msgnum.trim().parse().unwrap_or(0),
+            total: end.trim().parse().unwrap_or(0),
+            last_done,
+        }
+    }
+
+    fn read(state_dir: &Path, comment: &str) -> Self {
+        let read = |name: &str| std::fs::read_to_string(state_dir.join(name)).unwrap_or_default();
+        // `rebase-apply` counts patches in `next` and `last`, and has no todo
+        if state_dir.join("msgnum").exists() {
+            Self::parse(&read("msgnum"), &read("end"), &read("done"), comment)
+        } else {
+            Self::parse(&read("next"), &read("last"), "", comment)
+        }
+    }
+}
+
+fn git_rebase(args: &[String]) -> color_eyre::Result<bool> {
+    let exe = std::env::current_exe()?;
+    // git runs the sequence editor through the shell
+    let sequence_editor = format!("{} --editor rebase", quote(&exe.to_string_lossy()));
+
+    let status = Command::new("git")
+        .arg("rebase")
+        .args(args)
+        .env("GIT_SEQUENCE_EDITOR", sequence_editor)
+        .status()?;
+
+    Ok(status.success())
+}
+
+/// The directory git keeps rebase state in, if a rebase is in progress.
+fn state_dir(repo: &Repository) -> Option<PathBuf> {
+    ["rebase-merge", "rebase-apply"]
+        .iter()
+        .map(|name| repo.path().join(name))
+        .find(|dir| dir.is_dir())
+}
+
+fn report_stop(repo: &Repository, state_dir: &Path) -> color_eyre::Result<()> {
+    let progress = RebaseProgress::read(state_dir, &comment_string(repo));
+    let index = repo.index()?;
+    let reason = StopReason::classify(progress.last_done.as_deref(), index.has_conflicts());
+
+    println!();
+    print!(
+        "Stopped at {}/{} for {}",
+        progress.step,
+        progress.total,
+        reason.describe()
+    );
+    match &progress.last_done {
+        Some(line) => println!(": {}", line),
+        None => println!(),
+    }
+
+    if reason == StopReason::Conflict {
+        for conflict in index.conflicts()?.flatten() {
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(entry) = entry {
+                println!("  conflict: {}", String::from_utf8_lossy(&entry.path));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn report_done(repo: &Repository) -> color_eyre::Result<()> {
+    let head = repo.head()?.peel_to_commit()?;
+    let id = head.as_object().short_id()?;
+    println!(
+        "Rebase complete, HEAD is now {} {}",
+        id.as_str().unwrap_or_default(),
+        head.summary().unwrap_or_default()
+    );
+    Ok(())
+}
+
+fn abort() -> color_eyre::Result<()> {
+    if !git_rebase(&["--abort".to_string()])? {
+        return Err(eyre!("git rebase --abort failed"));
+    }
+    println!("Rebase aborted");
+    Ok(())
+}
+
+fn prompt() -> color_eyre::Result<String> {
+    print!("[c]ontinue  [s]kip  s[h]ell  [a]bort  [q]uit > ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase())
+}
+
+/// Run the rebase and stay attached until it completes, is aborted, or the
+/// user leaves it stopped with `q`.
+pub fn run(args: RebaseArgs) -> color_eyre::Result<()> {
+    if args.abort {
+        return abort();
+    }
+
+    let mut rebase_args = vec![];
+    if args.continue_rebase {
+        rebase_args.push("--continue".to_string());
+    } else {
+        if args.interactive {
+            rebase_args.push("--interactive".to_string());
+        }
+        rebase_args.extend(args.args);
+        rebase_args.extend(args.upstream);
+    }
+
+    let mut success = git_rebase(&rebase_args)?;
+
+    loop {
+        let repo = Repository::discover(std::env::current_dir()?)?;
+        let Some(state_dir) = state_dir(&repo) else {
+            if !success {
+                return Err(eyre!("git rebase failed"));
+            }
+            return report_done(&repo);
+        };
+
+        report_stop(&repo, &state_dir)?;
+
+        success = match prompt()?.as_str() {
+            "c" | "continue" => git_rebase(&["--continue".to_string()])?,
+            "s" | "skip" => git_rebase(&["--skip".to_string()])?,
+            "h" | "shell" => {
+                let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+                Command::new(shell).status()?;
+                true
+            }
+            "a" | "abort" => return abort(),
+            "q" | "quit" => {
+                println!("Leaving the rebase stopped, resume with `glitt rebase --continue`");
+                return Ok(());
+            }
+            _ => true,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editors::rebase::test_repo::TestRepo;
+
+    #[test]
+    fn parse_progress() {
+        let progress = RebaseProgress::parse(
+            "2\n",
+            "5\n",
+            "pick 1234567 First\nedit 89abcde Second\n",
+            "#",
+        );
+        assert_eq!(
+            progress,
+            RebaseProgress {
+                step: 2,
+                total: 5,
+                last_done: Some("edit 89abcde Second".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn read_progress_of_rebase_apply() {
+        let test_repo = TestRepo::new("rebase-apply");
+        let dir = test_repo.repo.path().join("rebase-apply");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("next"), "3\n").unwrap();
+        std::fs::write(dir.join("last"), "4\n").unwrap();
+
+        assert_eq!(
+            RebaseProgress::read(&dir, "#"),
+            RebaseProgress {
+                step: 3,
+                total: 4,
+                last_done: None,
+            }
+        );
+    }
+
+    #[test]
+    fn classify_stops() {
+        assert_eq!(
+            StopReason::classify(Some("edit 1234567 First"), false),
+            StopReason::Edit
+        );
+        assert_eq!(
+            StopReason::classify(Some("e 1234567 First"), true),
+            StopReason::Conflict
+        );
+        assert_eq!(
+            StopReason::classify(Some("break"), false),
+            StopReason::Break
+        );
+        assert_eq!(
+            StopReason::classify(Some("exec make test"), false),
+            StopReason::ExecFailed
+        );
+        assert_eq!(StopReason::classify(None, false), StopReason::Other);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::editors::{
+    Editor,
+    diff::diff_lines,
+    message::{CommitMessage, MessageInput},
+    rebase::todo::comment_string,
+};
+use git2::{Index, Repository};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+pub struct CommitEditor {
+    path: PathBuf,
+    message: CommitMessage,
+    input: MessageInput,
+    diff: Vec<Line<'static>>,
+    /// Whether the commit replaces HEAD, so the diff is against its parent
+    amend: bool,
+    diff_scroll: u16,
+}
+
+impl CommitEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let comment = comment_string(&repo);
+        let message = CommitMessage::parse(&content, &comment);
+        let input = MessageInput::new(&message);
+
+        let amend = Self::is_amend(&repo, &message, &comment);
+        let diff = Self::get_staged_diff(&repo, amend).unwrap_or_default();
+
+        Ok(Self {
+            path,
+            message,
+            input,
+            diff,
+            amend,
+            diff_scroll: 0,
+        })
+    }
+
+    /// Whether git is amending HEAD. Callers such as rebase say so in
+    /// `GIT_REFLOG_ACTION`, while `git commit --amend` shows the author
+    /// date it reuses in the template and exports it as `GIT_AUTHOR_DATE`.
+    fn is_amend(repo: &Repository, message: &CommitMessage, comment: &str) -> bool {
+        if std::env::var("GIT_REFLOG_ACTION").is_ok_and(|action| action.contains("amend")) {
+            return true;
+        }
+
+        let shows_date = message.comments.iter().any(|line| {
+            line.strip_prefix(comment)
+                .is_some_and(|line| line.trim_start().starts_with("Date:"))
+        });
+        let Some(head) = repo.head().ok().and_then(|head| head.peel_to_commit().ok()) else {
+            return false;
+        };
+
+        // The exported date is `@<seconds> <offset>`
+        let author_date = std::env::var("GIT_AUTHOR_DATE").ok().and_then(|date| {
+            date.trim_start_matches('@')
+                .split_whitespace()
+                .next()
+                .and_then(|seconds| seconds.parse::<i64>().ok())
+        });
+        shows_date && author_date.is_none_or(|seconds| seconds == head.author().when().seconds())
+    }
+
+    /// The changes that are about to be committed, or for an amend the
+    /// changes the amended commit will contain. `git commit -a` and
+    /// `git commit <paths>` stage into a temporary index which git exposes
+    /// through `GIT_INDEX_FILE`, so prefer that over the repository index.
+    fn get_staged_diff(repo: &Repository, amend: bool) -> Option<Vec<Line<'static>>> {
+        let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let base = match (&head, amend) {
+            (Some(head), true) => head.parent(0).ok(),
+            (head, _) => head.clone(),
+        };
+        let head_tree = base.and_then(|commit| commit.tree().ok());
+
+        let index = match std::env::var_os("GIT_INDEX_FILE") {
+            Some(path) => Index::open(Path::new(&path)).ok()?,
+            None => repo.index().ok()?,
+        };
+
+        let diff = repo
+            .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+            .ok()?;
+
+        diff_lines(&diff)
+    }
+
+    pub fn scroll_down(&mut self) {
+        let max = self.diff.len().saturating_sub(1) as u16;
+        self.diff_scroll = (self.diff_scroll + 10).min(max);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.diff_scroll = self.diff_scroll.saturating_sub(10);
+    }
+
+    pub fn save(&mut self) -> Result<(), color_eyre::Report> {
+        self.input.apply(&mut self.message);
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    /// An empty message makes git abort the commit.
+    pub fn save_empty(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    pub fn render_diff(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let title = if self.amend {
+            "Changes in the amended commit"
+        } else {
+            "Staged changes since HEAD"
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+
+        let paragraph = if self.diff.is_empty() {
+            Paragraph::new("No staged changes")
+        } else {
+            Paragraph::new(self.diff.clone()).scroll((self.diff_scroll, 0))
+        };
+
+        frame.render_widget(paragraph.block(block), area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Switch field  {} Scroll diff  {} save and quit  {} abort",
+            "Tab".bold(),
+            "PgUp/PgDn".bold(),
+            "^s".bold(),
+            "^c".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for CommitEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("COMMIT_EDITMSG"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Max(76), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.input.render(frame, editor_area[0], true);
+        self.render_diff(frame, editor_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_empty()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Tab | KeyCode::BackTab,
+                    ..
+                }) => self.input.toggle_focus(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageDown,
+                    ..
+                }) => self.scroll_down(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageUp,
+                    ..
+                }) => self.scroll_up(),
+
+                Event::Key(key) => {
+                    self.input.handle_key(key);
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
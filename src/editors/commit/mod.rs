@@ -0,0 +1,3 @@
+pub mod editor;
+
+pub use editor::CommitEditor;
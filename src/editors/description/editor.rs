@@ -0,0 +1,231 @@
+use crate::editors::{
+    Editor,
+    message::{CommitMessage, MessageInput},
+    rebase::todo::comment_string,
+};
+use git2::{BranchType, Repository, Sort};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+/// The commits a branch adds on top of its upstream.
+struct BranchCommits {
+    upstream: Option<String>,
+    commits: Vec<(String, String)>,
+}
+
+pub struct DescriptionEditor {
+    path: PathBuf,
+    branch: String,
+    message: CommitMessage,
+    input: MessageInput,
+    commits: Option<BranchCommits>,
+    commits_scroll: u16,
+}
+
+impl DescriptionEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let comment = comment_string(&repo);
+        let message = CommitMessage::parse(&content, &comment);
+        let branch = message
+            .comment_after("Please edit the description for the branch", &comment)
+            .unwrap_or_default()
+            .to_string();
+        let input = MessageInput::new(&message);
+
+        let commits = Self::get_branch_commits(&repo, &branch).ok();
+
+        Ok(Self {
+            path,
+            branch,
+            message,
+            input,
+            commits,
+            commits_scroll: 0,
+        })
+    }
+
+    /// Walk the branch, hiding everything reachable from its upstream. A
+    /// branch without an upstream lists all of its history.
+    fn get_branch_commits(repo: &Repository, name: &str) -> Result<BranchCommits, git2::Error> {
+        let branch = repo.find_branch(name, BranchType::Local)?;
+        let upstream = branch.upstream().ok();
+
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL)?;
+        walk.push(branch.get().peel_to_commit()?.id())?;
+        if let Some(upstream) = &upstream {
+            walk.hide(upstream.get().peel_to_commit()?.id())?;
+        }
+
+        let mut commits = vec![];
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            let id = commit.as_object().short_id()?;
+            commits.push((
+                id.as_str().unwrap_or_default().to_string(),
+                commit.summary().unwrap_or_default().to_string(),
+            ));
+        }
+
+        let upstream = match upstream {
+            Some(upstream) => upstream.name()?.map(str::to_string),
+            None => None,
+        };
+
+        Ok(BranchCommits { upstream, commits })
+    }
+
+    pub fn scroll_down(&mut self) {
+        let len = self.commits.as_ref().map_or(0, |c| c.commits.len());
+        let max = len.saturating_sub(1) as u16;
+        self.commits_scroll = (self.commits_scroll + 10).min(max);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.commits_scroll = self.commits_scroll.saturating_sub(10);
+    }
+
+    pub fn save(&mut self) -> Result<(), color_eyre::Report> {
+        self.input.apply(&mut self.message);
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    /// An empty description removes the branch description entirely, so
+    /// aborting writes the original file back untouched.
+    pub fn save_original(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    pub fn render_commits(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let (title, lines) = match &self.commits {
+            Some(BranchCommits { upstream, commits }) => {
+                let title = match upstream {
+                    Some(upstream) => format!(
+                        "{} commits on {} since {}",
+                        commits.len(),
+                        self.branch,
+                        upstream
+                    ),
+                    None => format!("{} commits on {} (no upstream)", commits.len(), self.branch),
+                };
+                let lines = commits
+                    .iter()
+                    .map(|(id, summary)| {
+                        Line::from(vec![
+                            Span::styled(id.clone(), Style::default().fg(Color::Yellow)),
+                            Span::raw(" "),
+                            Span::raw(summary.clone()),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                (title, lines)
+            }
+            None => (
+                "Commits".to_string(),
+                vec![Line::from(format!("Branch {} not found", self.branch))],
+            ),
+        };
+
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = Paragraph::new(lines)
+            .scroll((self.commits_scroll, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Switch field  {} Scroll commits  {} save and quit  {} abort",
+            "Tab".bold(),
+            "PgUp/PgDn".bold(),
+            "^s".bold(),
+            "^c".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for DescriptionEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("EDIT_DESCRIPTION"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Max(76), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.input.render(frame, editor_area[0], true);
+        self.render_commits(frame, editor_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_original()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Tab | KeyCode::BackTab,
+                    ..
+                }) => self.input.toggle_focus(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageDown,
+                    ..
+                }) => self.scroll_down(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageUp,
+                    ..
+                }) => self.scroll_up(),
+
+                Event::Key(key) => {
+                    self.input.handle_key(key);
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::DateTime;
+use git2::{Commit, Diff, DiffFormat, Repository};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Style},
+    text::Line,
+    widgets::Paragraph,
+};
+
+pub fn normalize_diff_line(line: &str) -> String {
+    line.replace('\t', "  ")
+}
+
+/// Render a diff as patch text, coloring added and removed lines.
+pub fn diff_lines(diff: &Diff) -> Option<Vec<Line<'static>>> {
+    let mut lines = vec![];
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        let style = match line.origin() {
+            '+' => Style::default().fg(Color::Green),
+            '-' => Style::default().fg(Color::Red),
+            _ => Style::default(),
+        };
+
+        let chunk = str::from_utf8(line.content()).unwrap_or_default();
+        for line in chunk.lines() {
+            let line = Line::from(normalize_diff_line(line))
+                .style(style)
+                .alignment(Alignment::Left);
+            lines.push(line);
+        }
+        true
+    })
+    .ok()?;
+
+    Some(lines)
+}
+
+/// The changes a commit makes relative to its first parent, or to the empty
+/// tree for a root commit.
+pub fn commit_diff(repo: &Repository, commit: &Commit) -> Option<Vec<Line<'static>>> {
+    let tree = commit.tree().ok()?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .ok()?;
+
+    diff_lines(&diff)
+}
+
+/// A commit's author, date, message and diff, in the style of `git show`.
+pub fn format_commit(repo: &Repository, commit: &Commit) -> Paragraph<'static> {
+    let timestamp = DateTime::from_timestamp(commit.time().seconds(), 0)
+        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+
+    let diff = commit_diff(repo, commit).unwrap_or_default();
+
+    let mut content = vec![];
+    content.push(
+        format!(
+            "Author: {} <{}>\n",
+            commit.author().name().unwrap_or("Unknown"),
+            commit.author().email().unwrap_or("unknown")
+        )
+        .into(),
+    );
+    content.push(format!("Date:   {}\n\n", timestamp).into());
+    content.push("".into());
+    content.push(format!("{}\n\n", commit.message().unwrap_or("No commit message")).into());
+    content.push("".into());
+
+    content.extend(diff);
+
+    Paragraph::new(content).style(Style::default())
+}
@@ -0,0 +1,214 @@
+use crate::editors::{
+    Editor,
+    hunk::patch::{HunkPatch, PatchLine},
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+pub struct HunkEditor {
+    path: PathBuf,
+    patch: HunkPatch,
+    list_state: ListState,
+}
+
+impl HunkEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let patch = HunkPatch::parse(&content);
+
+        let initial_line = patch
+            .lines()
+            .iter()
+            .position(PatchLine::is_change)
+            .unwrap_or(0);
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(initial_line));
+
+        Ok(Self {
+            path,
+            patch,
+            list_state,
+        })
+    }
+
+    fn selected(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    pub fn move_cursor_down(&mut self) {
+        let lines = self.patch.lines();
+        let next = (self.selected() + 1..lines.len()).find(|&i| lines[i].is_change());
+        if let Some(idx) = next {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    pub fn move_cursor_up(&mut self) {
+        let lines = self.patch.lines();
+        let previous = (0..self.selected()).rev().find(|&i| lines[i].is_change());
+        if let Some(idx) = previous {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    pub fn toggle_current_line(&mut self) {
+        self.patch.toggle(self.selected());
+    }
+
+    pub fn save(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, self.patch.to_string())?;
+        Ok(())
+    }
+
+    /// An empty hunk makes git abort the edit and leave the hunk unchanged.
+    pub fn save_empty(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    pub fn render_lines(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("Hunk").borders(Borders::ALL);
+        let selected = self.selected();
+
+        let items: Vec<ListItem> = self
+            .patch
+            .lines()
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let marker = match line {
+                    PatchLine::Added { included, .. } | PatchLine::Removed { included, .. } => {
+                        if *included {
+                            "[x] "
+                        } else {
+                            "[ ] "
+                        }
+                    }
+                    _ => "    ",
+                };
+
+                let mut style = line.get_style();
+                if i == selected {
+                    style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+                }
+
+                ListItem::new(Line::from(format!("{}{}", marker, line))).style(style)
+            })
+            .collect();
+
+        let list = List::new(items).block(block);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    pub fn render_preview(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("Patch").borders(Borders::ALL);
+
+        let lines = self
+            .patch
+            .resolved()
+            .into_iter()
+            .filter(|line| !matches!(line, PatchLine::Comment(_)))
+            .map(|line| {
+                let style = match line {
+                    PatchLine::Context(_) => Style::default().fg(Color::Gray),
+                    _ => line.get_style(),
+                };
+                Line::from(line.to_string()).style(style)
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Move  {} toggle line  {} include all  {} exclude all  {} quit and save  {} abort",
+            "↑/↓".bold(),
+            "space".bold(),
+            "i".bold(),
+            "x".bold(),
+            "q".bold(),
+            "a".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for HunkEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|f| f.eq("addp-hunk-edit.diff"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.render_lines(frame, editor_area[0]);
+        self.render_preview(frame, editor_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }) => self.move_cursor_down(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => self.move_cursor_up(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(' '),
+                    ..
+                }) => self.toggle_current_line(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('i'),
+                    ..
+                }) => self.patch.set_all(true),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('x'),
+                    ..
+                }) => self.patch.set_all(false),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('q'),
+                    ..
+                }) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('a'),
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_empty()?;
+                    return Ok(());
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod editor;
+pub mod patch;
+
+pub use editor::HunkEditor;
@@ -0,0 +1,419 @@
+use std::fmt::Display;
+
+use ratatui::style::{Color, Modifier, Style};
+
+/// The guide git appends when the patch is applied in reverse, as with
+/// `git reset -p` or `git checkout -p`.
+const REVERSED_GUIDE: &str = "To remove '+' lines, make them ' ' lines (context).";
+
+/// A `@@ -old_start,old_count +new_start,new_count @@ context` header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HunkHeader {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub context: String,
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+impl HunkHeader {
+    pub fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix("@@ -")?;
+        let (ranges, context) = rest.split_once(" @@")?;
+        let (old, new) = ranges.split_once(" +")?;
+        let (old_start, old_count) = parse_range(old)?;
+        let (new_start, new_count) = parse_range(new)?;
+
+        Some(HunkHeader {
+            old_start,
+            old_count,
+            new_start,
+            new_count,
+            context: context.to_string(),
+        })
+    }
+}
+
+impl Display for HunkHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@@ -{},{} +{},{} @@{}",
+            self.old_start, self.old_count, self.new_start, self.new_count, self.context
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchLine {
+    Comment(String),
+    Header(HunkHeader),
+    Context(String),
+    Added {
+        text: String,
+        included: bool,
+    },
+    Removed {
+        text: String,
+        included: bool,
+    },
+    /// `\ No newline at end of file`, which belongs to the line before it
+    NoNewline(String),
+}
+
+impl PatchLine {
+    pub fn parse(line: &str) -> Self {
+        if let Some(header) = HunkHeader::parse(line) {
+            return PatchLine::Header(header);
+        }
+
+        match line.chars().next() {
+            Some('+') => PatchLine::Added {
+                text: line[1..].to_string(),
+                included: true,
+            },
+            Some('-') => PatchLine::Removed {
+                text: line[1..].to_string(),
+                included: true,
+            },
+            Some(' ') => PatchLine::Context(line[1..].to_string()),
+            Some('\\') => PatchLine::NoNewline(line.to_string()),
+            // git strips trailing whitespace from empty context lines in
+            // some configurations, leaving a bare empty line
+            None => PatchLine::Context(String::new()),
+            _ => PatchLine::Comment(line.to_string()),
+        }
+    }
+
+    pub fn is_change(&self) -> bool {
+        matches!(self, PatchLine::Added { .. } | PatchLine::Removed { .. })
+    }
+
+    pub fn get_style(&self) -> Style {
+        match self {
+            PatchLine::Comment(_) | PatchLine::NoNewline(_) => {
+                Style::default().add_modifier(Modifier::DIM)
+            }
+            PatchLine::Header(_) => Style::default().fg(Color::Cyan),
+            PatchLine::Context(_) => Style::default(),
+            PatchLine::Added { included, .. } | PatchLine::Removed { included, .. }
+                if !included =>
+            {
+                Style::default()
+                    .add_modifier(Modifier::DIM)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            }
+            PatchLine::Added { .. } => Style::default().fg(Color::Green),
+            PatchLine::Removed { .. } => Style::default().fg(Color::Red),
+        }
+    }
+}
+
+impl Display for PatchLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchLine::Comment(line) | PatchLine::NoNewline(line) => write!(f, "{}", line),
+            PatchLine::Header(header) => write!(f, "{}", header),
+            PatchLine::Context(text) => write!(f, " {}", text),
+            PatchLine::Added { text, .. } => write!(f, "+{}", text),
+            PatchLine::Removed { text, .. } => write!(f, "-{}", text),
+        }
+    }
+}
+
+/// The hunk git asks the user to edit during `git add -p` and friends.
+///
+/// Changed lines can be excluded instead of hand-edited. On save excluded
+/// lines are turned into context or removed, depending on the direction the
+/// patch will be applied in, and the hunk headers are recounted so the
+/// result always applies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HunkPatch {
+    lines: Vec<PatchLine>,
+    reversed: bool,
+}
+
+impl HunkPatch {
+    pub fn parse(content: &str) -> Self {
+        let lines = content.lines().map(PatchLine::parse).collect::<Vec<_>>();
+        let reversed = lines
+            .iter()
+            .any(|line| matches!(line, PatchLine::Comment(c) if c.contains(REVERSED_GUIDE)));
+
+        HunkPatch { lines, reversed }
+    }
+
+    pub fn lines(&self) -> &[PatchLine] {
+        &self.lines
+    }
+
+    pub fn toggle(&mut self, idx: usize) {
+        if let Some(PatchLine::Added { included, .. } | PatchLine::Removed { included, .. }) =
+            self.lines.get_mut(idx)
+        {
+            *included = !*included;
+        }
+    }
+
+    pub fn set_all(&mut self, value: bool) {
+        for line in &mut self.lines {
+            if let PatchLine::Added { included, .. } | PatchLine::Removed { included, .. } = line {
+                *included = value;
+            }
+        }
+    }
+
+    /// The line as it will be written: excluded lines either become
+    /// context or are dropped.
+    fn resolve(&self, line: &PatchLine) -> Option<PatchLine> {
+        match line {
+            PatchLine::Added {
+                text,
+                included: false,
+            } if self.reversed => Some(PatchLine::Context(text.clone())),
+            PatchLine::Removed {
+                text,
+                included: false,
+            } if !self.reversed => Some(PatchLine::Context(text.clone())),
+            PatchLine::Added {
+                included: false, ..
+            }
+            | PatchLine::Removed {
+                included: false, ..
+            } => None,
+            line => Some(line.clone()),
+        }
+    }
+
+    /// Whether a later line of the same hunk is written, so the line before
+    /// it cannot be the end of the file.
+    fn followed_in_hunk(&self, idx: usize) -> bool {
+        self.lines[idx + 1..]
+            .iter()
+            .take_while(|line| !matches!(line, PatchLine::Header(_)))
+            .any(|line| {
+                matches!(
+                    self.resolve(line),
+                    Some(
+                        PatchLine::Context(_) | PatchLine::Added { .. } | PatchLine::Removed { .. }
+                    )
+                )
+            })
+    }
+
+    /// An excluded line without a newline cannot become context when later
+    /// lines are written, since context without a newline ends the file.
+    /// The change is kept instead, and the same line with a newline is
+    /// returned to be written after the marker.
+    fn restore_newline(&self, idx: usize) -> Option<(PatchLine, PatchLine)> {
+        let lines = match &self.lines[idx] {
+            PatchLine::Removed {
+                text,
+                included: false,
+            } if !self.reversed => (
+                PatchLine::Removed {
+                    text: text.clone(),
+                    included: true,
+                },
+                PatchLine::Added {
+                    text: text.clone(),
+                    included: true,
+                },
+            ),
+            PatchLine::Added {
+                text,
+                included: false,
+            } if self.reversed => (
+                PatchLine::Added {
+                    text: text.clone(),
+                    included: true,
+                },
+                PatchLine::Removed {
+                    text: text.clone(),
+                    included: true,
+                },
+            ),
+            _ => return None,
+        };
+
+        let marked = matches!(self.lines.get(idx + 1), Some(PatchLine::NoNewline(_)));
+        (marked && self.followed_in_hunk(idx + 1)).then_some(lines)
+    }
+
+    /// Add a line to the resolved patch, counting it in its hunk header.
+    fn push(lines: &mut Vec<PatchLine>, header_idx: &mut Option<usize>, line: PatchLine) {
+        match &line {
+            PatchLine::Header(_) => *header_idx = Some(lines.len()),
+            PatchLine::Context(_) | PatchLine::Added { .. } | PatchLine::Removed { .. } => {
+                if let Some(PatchLine::Header(header)) = header_idx.map(|i| &mut lines[i]) {
+                    if !matches!(line, PatchLine::Added { .. }) {
+                        header.old_count += 1;
+                    }
+                    if !matches!(line, PatchLine::Removed { .. }) {
+                        header.new_count += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        match line {
+            PatchLine::Header(header) => lines.push(PatchLine::Header(HunkHeader {
+                old_count: 0,
+                new_count: 0,
+                ..header
+            })),
+            line => lines.push(line),
+        }
+    }
+
+    /// The patch with excluded lines resolved and headers recounted.
+    pub fn resolved(&self) -> Vec<PatchLine> {
+        let mut lines: Vec<PatchLine> = vec![];
+        let mut header_idx = None;
+        let mut dropped_previous = false;
+        let mut restored = None;
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            if matches!(line, PatchLine::NoNewline(_)) && dropped_previous {
+                continue;
+            }
+
+            let resolved = match self.restore_newline(idx) {
+                Some((kept, with_newline)) => {
+                    restored = Some(with_newline);
+                    Some(kept)
+                }
+                None => self.resolve(line),
+            };
+            dropped_previous = resolved.is_none();
+            let Some(resolved) = resolved else {
+                continue;
+            };
+
+            let marker = matches!(resolved, PatchLine::NoNewline(_));
+            Self::push(&mut lines, &mut header_idx, resolved);
+            if let Some(line) = restored.take_if(|_| marker) {
+                Self::push(&mut lines, &mut header_idx, line);
+            }
+        }
+
+        lines
+    }
+}
+
+impl Display for HunkPatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.resolved() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HUNK: &str = "# Manual hunk edit mode -- see bottom for a quick guide.
+@@ -1,3 +1,4 @@ fn main
+ a
+ BB
+-c
++new1
++new2
+# ---
+# To remove '-' lines, make them ' ' lines (context).
+# To remove '+' lines, delete them.
+";
+
+    #[test]
+    fn parse_header() {
+        let header = HunkHeader::parse("@@ -10 +12,3 @@ fn main").unwrap();
+        assert_eq!(
+            header,
+            HunkHeader {
+                old_start: 10,
+                old_count: 1,
+                new_start: 12,
+                new_count: 3,
+                context: " fn main".to_string(),
+            }
+        );
+        assert_eq!(header.to_string(), "@@ -10,1 +12,3 @@ fn main");
+    }
+
+    #[test]
+    fn unchanged_patch_round_trips() {
+        let patch = HunkPatch::parse(HUNK);
+        assert_eq!(patch.to_string(), HUNK);
+    }
+
+    #[test]
+    fn excluded_removal_becomes_context() {
+        let mut patch = HunkPatch::parse(HUNK);
+        patch.toggle(4);
+        let rendered = patch.to_string();
+        assert!(rendered.contains("@@ -1,3 +1,5 @@ fn main\n a\n BB\n c\n+new1\n+new2\n"));
+    }
+
+    #[test]
+    fn excluded_addition_is_dropped() {
+        let mut patch = HunkPatch::parse(HUNK);
+        patch.toggle(6);
+        let rendered = patch.to_string();
+        assert!(rendered.contains("@@ -1,3 +1,3 @@ fn main\n a\n BB\n-c\n+new1\n#"));
+    }
+
+    #[test]
+    fn reversed_patch_swaps_rules() {
+        let content =
+            "@@ -1,2 +1,2 @@\n-old\n+new\n# To remove '+' lines, make them ' ' lines (context).\n";
+        let mut patch = HunkPatch::parse(content);
+        patch.set_all(false);
+        assert_eq!(
+            patch.to_string(),
+            "@@ -1,1 +1,1 @@\n new\n# To remove '+' lines, make them ' ' lines (context).\n"
+        );
+    }
+
+    #[test]
+    fn no_newline_marker_follows_its_line() {
+        let content =
+            "@@ -1 +1 @@\n-old\n\\ No newline at end of file\n+new\n\\ No newline at end of file\n";
+        let mut patch = HunkPatch::parse(content);
+        patch.toggle(3);
+        assert_eq!(
+            patch.to_string(),
+            "@@ -1,1 +1,0 @@\n-old\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn excluded_line_without_newline_keeps_its_marker_before_changes() {
+        let content =
+            "@@ -1 +1 @@\n-old\n\\ No newline at end of file\n+new\n\\ No newline at end of file\n";
+        let mut patch = HunkPatch::parse(content);
+        patch.toggle(1);
+        assert_eq!(
+            patch.to_string(),
+            "@@ -1,1 +1,2 @@\n-old\n\\ No newline at end of file\n+old\n+new\n\\ No newline at end of file\n"
+        );
+
+        // Without a later line the context is still the end of the file
+        let content = "@@ -1 +0,0 @@\n-old\n\\ No newline at end of file\n";
+        let mut patch = HunkPatch::parse(content);
+        patch.toggle(1);
+        assert_eq!(
+            patch.to_string(),
+            "@@ -1,1 +0,1 @@\n old\n\\ No newline at end of file\n"
+        );
+    }
+}
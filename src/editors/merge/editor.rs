@@ -0,0 +1,308 @@
+use crate::editors::{
+    Editor,
+    diff::diff_lines,
+    merge::message::parse_conflicts,
+    message::{CommitMessage, MessageInput},
+    rebase::todo::comment_string,
+};
+use git2::{Commit, Oid, Repository};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+/// A commit being merged, with the diff from it to the merge result.
+pub struct MergeParent {
+    name: String,
+    summary: String,
+    diff: Vec<Line<'static>>,
+}
+
+pub struct MergeEditor {
+    path: PathBuf,
+    message: CommitMessage,
+    input: MessageInput,
+    parents: Vec<MergeParent>,
+    conflicts: Vec<String>,
+    selected_parent: usize,
+    diff_scroll: u16,
+}
+
+impl MergeEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let mut repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let comment = comment_string(&repo);
+        let message = CommitMessage::parse(&content, &comment);
+        let input = MessageInput::new(&message);
+        let conflicts = parse_conflicts(&content, &comment);
+
+        let parents = Self::get_parents(&mut repo);
+
+        Ok(Self {
+            path,
+            message,
+            input,
+            parents,
+            conflicts,
+            selected_parent: 0,
+            diff_scroll: 0,
+        })
+    }
+
+    /// `HEAD` followed by every commit in `MERGE_HEAD`. Parents that cannot
+    /// be read (an unborn `HEAD`, a missing `MERGE_HEAD`) are left out so the
+    /// message and conflicts can still be edited.
+    fn get_parents(repo: &mut Repository) -> Vec<MergeParent> {
+        let mut merge_heads = vec![];
+        let _ = repo.mergehead_foreach(|oid| {
+            merge_heads.push(*oid);
+            true
+        });
+
+        let mut commits = vec![];
+        let head = repo.head().ok().and_then(|head| {
+            let commit = head.peel_to_commit().ok()?;
+            Some((head.shorthand().unwrap_or("HEAD").to_string(), commit))
+        });
+        commits.extend(head);
+        for oid in merge_heads {
+            if let Ok(commit) = repo.find_commit(oid) {
+                commits.push((Self::get_commit_name(repo, oid), commit));
+            }
+        }
+
+        let index = repo.index().ok();
+        commits
+            .into_iter()
+            .map(|(name, commit)| {
+                let diff = index
+                    .as_ref()
+                    .and_then(|index| Self::get_result_diff(repo, &commit, index))
+                    .unwrap_or_default();
+                MergeParent {
+                    name,
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                    diff,
+                }
+            })
+            .collect()
+    }
+
+    /// A branch pointing at `oid`, or its abbreviated id.
+    fn get_commit_name(repo: &Repository, oid: Oid) -> String {
+        let branch = repo.branches(None).ok().and_then(|branches| {
+            branches.flatten().find_map(|(branch, _)| {
+                let target = branch.get().target()?;
+                let name = branch.name().ok()??;
+                (target == oid).then(|| name.to_string())
+            })
+        });
+
+        branch.unwrap_or_else(|| oid.to_string()[..8].to_string())
+    }
+
+    fn get_result_diff(
+        repo: &Repository,
+        parent: &Commit,
+        index: &git2::Index,
+    ) -> Option<Vec<Line<'static>>> {
+        let tree = parent.tree().ok()?;
+        let diff = repo
+            .diff_tree_to_index(Some(&tree), Some(index), None)
+            .ok()?;
+        diff_lines(&diff)
+    }
+
+    pub fn next_parent(&mut self) {
+        if self.parents.is_empty() {
+            return;
+        }
+        self.selected_parent = (self.selected_parent + 1) % self.parents.len();
+        self.diff_scroll = 0;
+    }
+
+    pub fn scroll_down(&mut self) {
+        let Some(parent) = self.parents.get(self.selected_parent) else {
+            return;
+        };
+        let len = parent.diff.len();
+        let max = len.saturating_sub(1) as u16;
+        self.diff_scroll = (self.diff_scroll + 10).min(max);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.diff_scroll = self.diff_scroll.saturating_sub(10);
+    }
+
+    pub fn save(&mut self) -> Result<(), color_eyre::Report> {
+        self.input.apply(&mut self.message);
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    /// An empty message makes git abort the merge commit.
+    pub fn save_empty(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    pub fn render_merge_info(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("Merge").borders(Borders::ALL);
+
+        let mut content = vec![Line::from("Parents:")];
+        if self.parents.is_empty() {
+            content.push(Line::from("  unavailable").fg(Color::DarkGray));
+        }
+        for (i, parent) in self.parents.iter().enumerate() {
+            let line = Line::from(format!("  {} {}", parent.name, parent.summary));
+            if i == self.selected_parent {
+                content.push(line.add_modifier(Modifier::BOLD).fg(Color::Blue));
+            } else {
+                content.push(line);
+            }
+        }
+
+        content.push(Line::from(""));
+        if self.conflicts.is_empty() {
+            content.push(Line::from("No conflicts"));
+        } else {
+            content.push(Line::from("Conflicts:"));
+            content.extend(
+                self.conflicts
+                    .iter()
+                    .map(|path| Line::from(format!("  {}", path)).fg(Color::Red)),
+            );
+        }
+
+        frame.render_widget(Paragraph::new(content).block(block), area);
+    }
+
+    pub fn render_diff(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let Some(parent) = self.parents.get(self.selected_parent) else {
+            let block = Block::default().title("Result").borders(Borders::ALL);
+            frame.render_widget(
+                Paragraph::new("No parents to compare with").block(block),
+                area,
+            );
+            return;
+        };
+        let block = Block::default()
+            .title(format!("Result vs {}", parent.name))
+            .borders(Borders::ALL);
+
+        let paragraph = if parent.diff.is_empty() {
+            Paragraph::new("No changes")
+        } else {
+            Paragraph::new(parent.diff.clone()).scroll((self.diff_scroll, 0))
+        };
+
+        frame.render_widget(paragraph.block(block), area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Switch field  {} Next parent  {} Scroll diff  {} save and quit  {} abort",
+            "Tab".bold(),
+            "^n".bold(),
+            "PgUp/PgDn".bold(),
+            "^s".bold(),
+            "^c".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for MergeEditor {
+    /// `git merge --edit` opens `MERGE_MSG`; committing a resolved conflict
+    /// opens `COMMIT_EDITMSG` while `MERGE_HEAD` is present.
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| {
+            f.eq("MERGE_MSG")
+                || (f.eq("COMMIT_EDITMSG") && path.with_file_name("MERGE_HEAD").exists())
+        })
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Max(76), Constraint::Fill(1)]).split(main_area[1]);
+
+        let info_height = (self.parents.len().max(1) + self.conflicts.len().max(1) + 4) as u16;
+        let context_area = Layout::vertical([Constraint::Length(info_height), Constraint::Fill(1)])
+            .split(editor_area[1]);
+
+        self.input.render(frame, editor_area[0], true);
+        self.render_merge_info(frame, context_area[0]);
+        self.render_diff(frame, context_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_empty()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('n'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => self.next_parent(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Tab | KeyCode::BackTab,
+                    ..
+                }) => self.input.toggle_focus(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageDown,
+                    ..
+                }) => self.scroll_down(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageUp,
+                    ..
+                }) => self.scroll_up(),
+
+                Event::Key(key) => {
+                    self.input.handle_key(key);
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/// The files git lists under the `Conflicts:` trailer of a merge message.
+///
+/// Older versions of git write the trailer uncommented, newer versions
+/// comment it out, so both forms are accepted.
+pub fn parse_conflicts(content: &str, comment: &str) -> Vec<String> {
+    let lines = content
+        .lines()
+        .skip_while(|line| line.trim_start_matches(comment).trim() != "Conflicts:")
+        .skip(1);
+
+    let mut conflicts = vec![];
+    for line in lines {
+        let line = line.strip_prefix(comment).unwrap_or(line);
+        match line.strip_prefix('\t') {
+            Some(path) if !path.trim().is_empty() => conflicts.push(path.trim().to_string()),
+            _ => break,
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commented_conflicts() {
+        let content = "Merge branch 'feature'\n\n# Conflicts:\n#\tf.txt\n#\tsrc/g.rs\n#\n# It looks like you may be committing a merge.\n";
+        assert_eq!(parse_conflicts(content, "#"), vec!["f.txt", "src/g.rs"]);
+        assert_eq!(
+            parse_conflicts(&content.replace('#', ";"), ";"),
+            vec!["f.txt", "src/g.rs"]
+        );
+    }
+
+    #[test]
+    fn parse_uncommented_conflicts() {
+        let content = "Merge branch 'feature'\n\nConflicts:\n\tf.txt\n";
+        assert_eq!(parse_conflicts(content, "#"), vec!["f.txt"]);
+    }
+
+    #[test]
+    fn no_conflicts() {
+        let content = "Merge branch 'feature'\n# Please enter a commit message\n";
+        assert!(parse_conflicts(content, "#").is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt::Display;
+
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders},
+};
+
+use crate::editors::textarea::TextArea;
+
+/// The marker git places before the verbose diff in a commit template.
+/// Everything after it is ignored by git.
+pub const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// A commit-like message as written by git for the editor: the message
+/// itself plus the commented template, which is kept verbatim on save.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommitMessage {
+    pub subject: String,
+    pub body: String,
+    pub comments: Vec<String>,
+}
+
+impl CommitMessage {
+    /// Parse a message, keeping lines that start with `comment` as the
+    /// template.
+    pub fn parse(content: &str, comment: &str) -> Self {
+        let mut message = vec![];
+        let mut comments = vec![];
+
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            if let Some(rest) = line.strip_prefix(comment) {
+                comments.push(line.to_string());
+                if rest.trim() == SCISSORS {
+                    comments.extend(lines.by_ref().map(str::to_string));
+                }
+            } else {
+                message.push(line);
+            }
+        }
+
+        let mut message = message
+            .into_iter()
+            .skip_while(|line| line.trim().is_empty());
+        let subject = message.next().unwrap_or_default().trim_end().to_string();
+        let body = message
+            .skip_while(|line| line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_end()
+            .to_string();
+
+        CommitMessage {
+            subject,
+            body,
+            comments,
+        }
+    }
+
+    /// The comment line following the one containing `label`, which is how
+    /// git names the tag, branch or object a template is for.
+    pub fn comment_after(&self, label: &str, comment: &str) -> Option<&str> {
+        let idx = self.comments.iter().position(|c| c.contains(label))?;
+        let value = self
+            .comments
+            .get(idx + 1)?
+            .trim_start_matches(comment)
+            .trim();
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// The message without the commented template.
+    pub fn text(&self) -> String {
+        if self.body.is_empty() {
+            self.subject.clone()
+        } else {
+            format!("{}\n\n{}", self.subject, self.body)
+        }
+    }
+}
+
+impl Display for CommitMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.text())?;
+        if !self.comments.is_empty() {
+            writeln!(f)?;
+            for comment in &self.comments {
+                writeln!(f, "{}", comment)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFocus {
+    Subject,
+    Body,
+}
+
+/// A subject line input stacked above a body text area.
+pub struct MessageInput {
+    subject: TextArea,
+    body: TextArea,
+    focus: MessageFocus,
+}
+
+impl MessageInput {
+    pub fn new(message: &CommitMessage) -> Self {
+        let mut subject = TextArea::single_line(&message.subject);
+        subject.move_to_end();
+
+        Self {
+            subject,
+            body: TextArea::new(&message.body),
+            focus: MessageFocus::Subject,
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        self.subject.text()
+    }
+
+    pub fn body(&self) -> String {
+        self.body.text().trim_end().to_string()
+    }
+
+    /// Copy the edited subject and body into `message`.
+    pub fn apply(&self, message: &mut CommitMessage) {
+        message.subject = self.subject().trim_end().to_string();
+        message.body = self.body();
+    }
+
+    pub fn focus(&self) -> MessageFocus {
+        self.focus
+    }
+
+    pub fn set_focus(&mut self, focus: MessageFocus) {
+        self.focus = focus;
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            MessageFocus::Subject => MessageFocus::Body,
+            MessageFocus::Body => MessageFocus::Subject,
+        };
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match (self.focus, key.code) {
+            (MessageFocus::Subject, KeyCode::Enter | KeyCode::Down) => {
+                self.focus = MessageFocus::Body;
+                true
+            }
+            (MessageFocus::Subject, _) => self.subject.handle_key(key),
+            (MessageFocus::Body, KeyCode::Up) if self.body.row() == 0 => {
+                self.focus = MessageFocus::Subject;
+                true
+            }
+            (MessageFocus::Body, _) => self.body.handle_key(key),
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut ratatui::Frame, area: Rect, focused: bool) {
+        let areas = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).split(area);
+
+        let length = self.subject.text().chars().count();
+        let subject_style = match length {
+            0..=50 => Style::default(),
+            51..=72 => Style::default().fg(Color::Yellow),
+            _ => Style::default().fg(Color::Red),
+        };
+        let subject_block = Block::default()
+            .title(format!("Subject ({})", length))
+            .title_style(subject_style)
+            .borders(Borders::ALL);
+        let body_block = Block::default().title("Body").borders(Borders::ALL);
+
+        self.subject.render(
+            frame,
+            areas[0],
+            subject_block,
+            focused && self.focus == MessageFocus::Subject,
+        );
+        self.body.render(
+            frame,
+            areas[1],
+            body_block,
+            focused && self.focus == MessageFocus::Body,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_subject_body_and_comments() {
+        let content = "Fix the thing\n\nLonger explanation\nof the fix.\n\n# Please enter the commit message\n# On branch main\n";
+        let message = CommitMessage::parse(content, "#");
+        assert_eq!(message.subject, "Fix the thing");
+        assert_eq!(message.body, "Longer explanation\nof the fix.");
+        assert_eq!(
+            message.comments,
+            vec!["# Please enter the commit message", "# On branch main"]
+        );
+    }
+
+    #[test]
+    fn parse_empty_template() {
+        let message = CommitMessage::parse("\n# Please enter the commit message\n", "#");
+        assert_eq!(message.subject, "");
+        assert_eq!(message.body, "");
+        assert_eq!(
+            message.to_string(),
+            "\n\n# Please enter the commit message\n"
+        );
+    }
+
+    #[test]
+    fn scissors_keeps_verbose_diff_verbatim() {
+        let content = format!(
+            "subject\n# {}\ndiff --git a/x b/x\n+added\n\n#not a comment\n",
+            SCISSORS
+        );
+        let message = CommitMessage::parse(&content, "#");
+        assert_eq!(message.subject, "subject");
+        assert_eq!(message.body, "");
+        assert_eq!(message.comments.len(), 5);
+        assert_eq!(message.comments[2], "+added");
+    }
+
+    #[test]
+    fn comment_after_label() {
+        let content = "\n#\n# Write a message for tag:\n#   v1.2.0\n# Lines starting with '#' will be ignored.\n";
+        let message = CommitMessage::parse(content, "#");
+        assert_eq!(
+            message.comment_after("Write a message for tag:", "#"),
+            Some("v1.2.0")
+        );
+        assert_eq!(message.comment_after("Lines starting", "#"), None);
+    }
+
+    #[test]
+    fn parse_with_comment_string() {
+        let content = "Fix the thing\n#1 is fixed\n\n; Please enter the commit message\n";
+        let message = CommitMessage::parse(content, ";");
+        assert_eq!(message.subject, "Fix the thing");
+        assert_eq!(message.body, "#1 is fixed");
+        assert_eq!(message.comments, vec!["; Please enter the commit message"]);
+    }
+
+    #[test]
+    fn round_trip_message() {
+        let message = CommitMessage {
+            subject: "subject".to_string(),
+            body: "body".to_string(),
+            comments: vec!["# comment".to_string()],
+        };
+        assert_eq!(message.to_string(), "subject\n\nbody\n\n# comment\n");
+        assert_eq!(CommitMessage::parse(&message.to_string(), "#"), message);
+    }
+}
@@ -1,12 +1,31 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use ratatui::DefaultTerminal;
 
+pub mod commit;
+pub mod description;
+pub mod diff;
+pub mod hunk;
+pub mod merge;
+pub mod message;
+pub mod notes;
 pub mod rebase;
+pub mod shell;
+pub mod squash;
+pub mod tag;
+pub mod textarea;
 
-#[derive(Clone, Debug, clap::ValueEnum)]
-enum EditorKind {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum EditorKind {
     Rebase,
+    Commit,
+    Squash,
+    Merge,
+    Hunk,
+    Sequencer,
+    Tag,
+    Description,
+    Notes,
 }
 
 pub trait Editor {
@@ -16,3 +35,71 @@ pub trait Editor {
     fn render(&mut self, frame: &mut ratatui::Frame);
     fn run(&mut self, terminal: DefaultTerminal) -> color_eyre::Result<()>;
 }
+
+/// An editor known to glitt, with how to detect and launch it.
+pub struct Registration {
+    pub kind: EditorKind,
+    pub should_run: fn(&Path) -> bool,
+    pub launch: fn(PathBuf, DefaultTerminal) -> color_eyre::Result<()>,
+}
+
+/// Every editor, in the order they are tried when detecting which editor to
+/// use. More specific editors come first, e.g. the squash and merge editors
+/// also handle `COMMIT_EDITMSG` and must win over the commit editor.
+pub const EDITORS: &[Registration] = &[
+    Registration {
+        kind: EditorKind::Rebase,
+        should_run: rebase::RebaseEditor::should_run,
+        launch: |path, terminal| rebase::RebaseEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Sequencer,
+        should_run: rebase::SequencerEditor::should_run,
+        launch: |path, terminal| rebase::SequencerEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Squash,
+        should_run: squash::SquashEditor::should_run,
+        launch: |path, terminal| squash::SquashEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Merge,
+        should_run: merge::MergeEditor::should_run,
+        launch: |path, terminal| merge::MergeEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Commit,
+        should_run: commit::CommitEditor::should_run,
+        launch: |path, terminal| commit::CommitEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Tag,
+        should_run: tag::TagEditor::should_run,
+        launch: |path, terminal| tag::TagEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Description,
+        should_run: description::DescriptionEditor::should_run,
+        launch: |path, terminal| description::DescriptionEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Notes,
+        should_run: notes::NotesEditor::should_run,
+        launch: |path, terminal| notes::NotesEditor::new(path)?.run(terminal),
+    },
+    Registration {
+        kind: EditorKind::Hunk,
+        should_run: hunk::HunkEditor::should_run,
+        launch: |path, terminal| hunk::HunkEditor::new(path)?.run(terminal),
+    },
+];
+
+/// The editor registered for `kind`.
+pub fn find_editor(kind: EditorKind) -> Option<&'static Registration> {
+    EDITORS.iter().find(|editor| editor.kind == kind)
+}
+
+/// The first editor that wants to handle `path`.
+pub fn detect_editor(path: &Path) -> Option<&'static Registration> {
+    EDITORS.iter().find(|editor| (editor.should_run)(path))
+}
@@ -0,0 +1,248 @@
+use crate::editors::{
+    Editor,
+    diff::format_commit,
+    message::{CommitMessage, MessageInput},
+    rebase::todo::comment_string,
+};
+use git2::{Oid, Repository};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_NOTES_REF: &str = "refs/notes/commits";
+
+pub struct NotesEditor {
+    path: PathBuf,
+    repo: Repository,
+    message: CommitMessage,
+    input: MessageInput,
+    object: Option<Oid>,
+    /// Notes on the same object from refs other than the one being edited
+    other_notes: Vec<(String, String)>,
+    commit_scroll: u16,
+}
+
+impl NotesEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let comment = comment_string(&repo);
+        let message = CommitMessage::parse(&content, &comment);
+        let input = MessageInput::new(&message);
+
+        // git describes the annotated object with `git show --stat` output
+        // in the comments, starting with `# commit <sha>` and any decoration
+        let object = message
+            .comments
+            .iter()
+            .filter_map(|c| {
+                c.trim_start_matches(comment.as_str())
+                    .trim()
+                    .strip_prefix("commit ")
+            })
+            .filter_map(|rest| rest.split_whitespace().next())
+            .find_map(|sha| Oid::from_str(sha).ok());
+
+        let other_notes = object
+            .map(|oid| Self::get_other_notes(&repo, oid))
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            repo,
+            message,
+            input,
+            object,
+            other_notes,
+            commit_scroll: 0,
+        })
+    }
+
+    /// The ref `git notes` writes to when no `--ref` is given.
+    fn current_notes_ref(repo: &Repository) -> String {
+        std::env::var("GIT_NOTES_REF")
+            .ok()
+            .or_else(|| {
+                repo.config()
+                    .ok()
+                    .and_then(|config| config.get_string("core.notesRef").ok())
+            })
+            .unwrap_or_else(|| DEFAULT_NOTES_REF.to_string())
+    }
+
+    fn get_other_notes(repo: &Repository, oid: Oid) -> Vec<(String, String)> {
+        let current = Self::current_notes_ref(repo);
+        let Ok(references) = repo.references_glob("refs/notes/*") else {
+            return vec![];
+        };
+
+        references
+            .flatten()
+            .filter_map(|reference| reference.name().map(str::to_string))
+            .filter(|name| *name != current)
+            .filter_map(|name| {
+                let note = repo.find_note(Some(&name), oid).ok()?;
+                let text = note.message()?.trim_end().to_string();
+                Some((name, text))
+            })
+            .collect()
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.commit_scroll = self.commit_scroll.saturating_add(10);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.commit_scroll = self.commit_scroll.saturating_sub(10);
+    }
+
+    pub fn save(&mut self) -> Result<(), color_eyre::Report> {
+        self.input.apply(&mut self.message);
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    /// An empty message makes git remove the note, so aborting writes the
+    /// existing note back untouched.
+    pub fn save_original(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    pub fn render_other_notes(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("Other Notes").borders(Borders::ALL);
+
+        let mut lines = vec![];
+        for (name, text) in &self.other_notes {
+            let name = name.strip_prefix("refs/notes/").unwrap_or(name);
+            lines.push(Line::from(name.to_string()).style(Style::default().fg(Color::Cyan)));
+            lines.extend(text.lines().map(|line| Line::from(format!("  {}", line))));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    pub fn render_commit(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let commit = self.object.and_then(|oid| self.repo.find_commit(oid).ok());
+
+        let Some(commit) = commit else {
+            let block = Block::default().title("Commit").borders(Borders::ALL);
+            let paragraph = Paragraph::new("Not annotating a commit").block(block);
+            frame.render_widget(paragraph, area);
+            return;
+        };
+
+        let title = format!("Commit {}", &commit.id().to_string()[..7]);
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let paragraph = format_commit(&self.repo, &commit)
+            .scroll((self.commit_scroll, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Switch field  {} Scroll commit  {} save and quit  {} abort",
+            "Tab".bold(),
+            "PgUp/PgDn".bold(),
+            "^s".bold(),
+            "^c".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for NotesEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("NOTES_EDITMSG"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Max(76), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.input.render(frame, editor_area[0], true);
+
+        if self.other_notes.is_empty() {
+            self.render_commit(frame, editor_area[1]);
+        } else {
+            let height = self
+                .other_notes
+                .iter()
+                .map(|(_, text)| text.lines().count() + 1)
+                .sum::<usize>() as u16
+                + 2;
+            let info_area = Layout::vertical([Constraint::Max(height), Constraint::Min(1)])
+                .split(editor_area[1]);
+
+            self.render_other_notes(frame, info_area[0]);
+            self.render_commit(frame, info_area[1]);
+        }
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_original()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Tab | KeyCode::BackTab,
+                    ..
+                }) => self.input.toggle_focus(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageDown,
+                    ..
+                }) => self.scroll_down(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::PageUp,
+                    ..
+                }) => self.scroll_up(),
+
+                Event::Key(key) => {
+                    self.input.handle_key(key);
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -1,62 +1,326 @@
 use crate::editors::{
     Editor,
-    rebase::todo::{RebaseTodo, RebaseTodoLine},
+    diff::format_commit,
+    message::{CommitMessage, MessageInput},
+    rebase::{
+        graph::TodoGraph,
+        history::History,
+        simulate::{BackgroundSimulator, Report},
+        todo::{RebaseTodo, RebaseTodoLine, comment_string},
+        validate::{MissingCommitsCheck, Problem, Severity, missing_commits, validate},
+    },
+    shell::{highlight, quote},
+    textarea::TextArea,
 };
-use chrono::DateTime;
-use git2::{Commit, DiffFormat, Repository};
+use git2::{Commit, Oid, Repository};
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    layout::{Alignment, Constraint, Layout, Rect},
-    style::{Color, Style, Stylize},
-    text::Line,
+    DefaultTerminal,
+    crossterm::{
+        event::{
+            self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+            MouseButton, MouseEvent, MouseEventKind,
+        },
+        execute,
+    },
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
     widgets::{
         Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
-        ScrollbarState,
+        ScrollbarState, Wrap,
     },
 };
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The kind of todo list being edited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TodoMode {
+    /// `git rebase -i`, where every command is available
+    Rebase,
+    /// A multi-commit `git cherry-pick` or `git revert`. git requires every
+    /// line to use the same command, so lines can only be reordered and
+    /// dropped, and dropped lines are removed on save.
+    Sequencer { revert: bool },
+}
+
+impl TodoMode {
+    /// The line a commit is restored to when it is picked again.
+    fn keep_line(&self, commit: String, rest: Vec<String>) -> RebaseTodoLine {
+        match self {
+            TodoMode::Sequencer { revert: true } => RebaseTodoLine::Revert { commit, rest },
+            _ => RebaseTodoLine::Pick { commit, rest },
+        }
+    }
+}
+
+/// What the exec input writes to when confirmed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecTarget {
+    /// A new exec after the selected line
+    Insert,
+    /// The exec line at this index
+    Edit(usize),
+    /// An exec after every commit, like `git rebase -x`
+    EveryCommit,
+}
+
+struct ExecInput {
+    target: ExecTarget,
+    textarea: TextArea,
+}
+
+/// Everything an undo restores.
+#[derive(Clone)]
+struct TodoSnapshot {
+    todo: RebaseTodo,
+    rewords: HashMap<String, CommitMessage>,
+    selected: usize,
+}
 
 pub struct RebaseEditor {
     path: PathBuf,
     todo: RebaseTodo,
     repo: Repository,
+    comment: String,
     list_state: ListState,
+    mode: TodoMode,
+    /// Number of leading lines that cannot be selected or moved
+    locked: usize,
+    /// The other end of the visual selection, while one is active
+    anchor: Option<usize>,
+    /// Whether the todo is drawn as branch lanes
+    graph_view: bool,
+    /// Set when saving was refused because of errors in the todo
+    save_blocked: bool,
+    /// The commit lines as loaded, to find commits whose lines were deleted
+    original_commits: Vec<RebaseTodoLine>,
+    missing_check: MissingCommitsCheck,
+    /// Whether saving is waiting on what to do with missing commits
+    missing_prompt: bool,
+    simulator: Option<BackgroundSimulator>,
+    /// The todo last sent to the simulator
+    requested: Option<String>,
+    /// The last finished simulation, along with the todo it was run on
+    simulation: Option<(String, Option<Report>)>,
+    /// Whether the right pane compares the result with the original branch
+    compare_view: bool,
+    exec_input: Option<ExecInput>,
+    /// Messages written up front for reworded commits, by commit
+    rewords: HashMap<String, CommitMessage>,
+    /// The commit whose message is being rewritten in the right pane
+    reword_input: Option<(String, CommitMessage, MessageInput)>,
+    history: History<TodoSnapshot>,
+    /// The todo from before a drag started, recorded as one change when the
+    /// drag ends
+    drag: Option<TodoSnapshot>,
+    commit_scroll: u16,
+    /// Where the todo list and commit pane were last drawn
+    todo_area: Rect,
+    commit_area: Rect,
+    /// Clickable instruction bar entries and the key each one presses
+    instruction_targets: Vec<(Rect, KeyEvent)>,
+}
+
+/// An instruction bar entry: the key label, what it does, and the key a
+/// click presses.
+type Instruction = (&'static str, &'static str, Option<KeyEvent>);
+
+fn key(code: KeyCode) -> Option<KeyEvent> {
+    Some(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn ctrl(c: char) -> Option<KeyEvent> {
+    Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
 }
 
 impl RebaseEditor {
     pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        Self::with_mode(path, TodoMode::Rebase)
+    }
+
+    pub fn with_mode(path: PathBuf, mode: TodoMode) -> Result<Self, color_eyre::Report> {
+        let git_dir = path
+            .parent()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?;
+        let repo = Repository::discover(git_dir)?;
+
         let content = std::fs::read_to_string(&path)?;
-        let todo = RebaseTodo::parse(&content);
+        let comment = comment_string(&repo);
+        let todo = RebaseTodo::parse(&content, &comment);
 
-        let initial_line = todo
-            .lines()
-            .iter()
-            .position(|line| !matches!(line, RebaseTodoLine::Comment { .. }))
-            .unwrap_or(0);
+        // While a cherry-pick or revert is stopped, the first line of the
+        // sequencer todo is the commit being resolved and must stay in place.
+        let locked = match mode {
+            TodoMode::Sequencer { .. }
+                if repo.path().join("CHERRY_PICK_HEAD").exists()
+                    || repo.path().join("REVERT_HEAD").exists() =>
+            {
+                1
+            }
+            _ => 0,
+        };
 
-        let repo = Repository::discover(
-            path.parent()
-                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
-        )?;
+        let original_commits = todo
+            .lines()
+            .filter(|line| line.get_commit().is_some())
+            .cloned()
+            .collect();
+        // git only checks for missing commits when rebasing
+        let missing_check = match mode {
+            TodoMode::Rebase => MissingCommitsCheck::from_repo(&repo),
+            TodoMode::Sequencer { .. } => MissingCommitsCheck::Ignore,
+        };
 
-        let mut list_state = ListState::default();
-        list_state.select(Some(initial_line));
+        // A rebase records where it started and the branch tip it started
+        // from, a cherry-pick or revert applies to HEAD. Once lines have run,
+        // as with `git rebase --edit-todo`, the rest applies to HEAD too.
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .ok()
+            .map(|commit| commit.id());
+        let read_oid = |name| {
+            std::fs::read_to_string(git_dir.join(name))
+                .ok()
+                .and_then(|oid| Oid::from_str(oid.trim()).ok())
+        };
+        let done = std::fs::read_to_string(git_dir.join("done")).unwrap_or_default();
+        let started = RebaseTodo::parse(&done, &comment)
+            .lines()
+            .any(|line| !matches!(line, RebaseTodoLine::Comment { .. }));
+        let onto = if started {
+            head
+        } else {
+            read_oid("onto").or(head)
+        };
+        let orig_head = read_oid("orig-head").or(head);
+        let simulator = onto.map(|onto| {
+            BackgroundSimulator::spawn(repo.path().to_path_buf(), comment.clone(), onto, orig_head)
+        });
 
-        Ok(Self {
+        let mut editor = Self {
             path,
             todo,
             repo,
-            list_state,
-        })
+            comment,
+            list_state: ListState::default(),
+            mode,
+            locked,
+            anchor: None,
+            graph_view: false,
+            save_blocked: false,
+            original_commits,
+            missing_check,
+            missing_prompt: false,
+            simulator,
+            requested: None,
+            simulation: None,
+            compare_view: false,
+            exec_input: None,
+            rewords: HashMap::new(),
+            reword_input: None,
+            history: History::default(),
+            drag: None,
+            commit_scroll: 0,
+            todo_area: Rect::default(),
+            commit_area: Rect::default(),
+            instruction_targets: vec![],
+        };
+
+        editor.graph_view = mode == TodoMode::Rebase && TodoGraph::new(&editor.todo).is_branched();
+
+        let initial_line = (0..editor.todo.len())
+            .find(|&idx| editor.is_selectable(idx))
+            .unwrap_or(0);
+        editor.list_state.select(Some(initial_line));
+
+        Ok(editor)
     }
 
     fn selected(&self) -> usize {
         self.list_state.selected().unwrap_or(0)
     }
 
+    fn is_selectable(&self, idx: usize) -> bool {
+        idx >= self.locked && !matches!(self.todo.get(idx), Some(RebaseTodoLine::Comment { .. }))
+    }
+
+    /// The lines verbs apply to: the visual selection, or the selected line.
+    fn selection(&self) -> RangeInclusive<usize> {
+        let selected = self.selected();
+        match self.anchor {
+            Some(anchor) => anchor.min(selected)..=anchor.max(selected),
+            None => selected..=selected,
+        }
+    }
+
+    /// Start a visual selection at the selected line, or end the current one.
+    pub fn toggle_visual(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.selected()),
+        };
+    }
+
+    fn snapshot(&self) -> TodoSnapshot {
+        TodoSnapshot {
+            todo: self.todo.clone(),
+            rewords: self.rewords.clone(),
+            selected: self.selected(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: TodoSnapshot) {
+        self.todo = snapshot.todo;
+        self.rewords = snapshot.rewords;
+        self.list_state.select(Some(snapshot.selected));
+        self.anchor = None;
+    }
+
+    /// Make a change to the todo, recording it in the history if anything
+    /// changed. Every edit goes through here so it can be undone.
+    fn apply(&mut self, label: impl Into<String>, change: impl FnOnce(&mut Self)) {
+        let before = self.snapshot();
+        change(self);
+        self.record(label, before);
+    }
+
+    fn record(&mut self, label: impl Into<String>, before: TodoSnapshot) {
+        if self.todo.to_string() != before.todo.to_string() || self.rewords != before.rewords {
+            self.history.record(label, before);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.history.undo(self.snapshot()) {
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.history.redo(self.snapshot()) {
+            self.restore(snapshot);
+        }
+    }
+
+    /// A short description of the selected line for history labels.
+    fn describe_current_line(&self) -> String {
+        match self.get_current_line() {
+            Some(line) => match line.get_commit() {
+                Some(commit) => commit.to_string(),
+                None => line.get_action().to_string(),
+            },
+            None => String::new(),
+        }
+    }
+
     pub fn move_cursor_down(&mut self) {
-        let lines = self.todo.lines();
-        let len = lines.len();
+        let len = self.todo.len();
         if len == 0 {
             return;
         }
@@ -64,16 +328,16 @@ impl RebaseEditor {
         let mut idx = self.selected();
         for _ in 0..len {
             idx = (idx + 1) % len;
-            if !matches!(lines[idx], RebaseTodoLine::Comment { .. }) {
+            if self.is_selectable(idx) {
                 self.list_state.select(Some(idx));
+                self.commit_scroll = 0;
                 return;
             }
         }
     }
 
     pub fn move_cursor_up(&mut self) {
-        let lines = self.todo.lines();
-        let len = lines.len();
+        let len = self.todo.len();
         if len == 0 {
             return;
         }
@@ -85,16 +349,20 @@ impl RebaseEditor {
             } else {
                 idx -= 1;
             }
-            if !matches!(lines[idx], RebaseTodoLine::Comment { .. }) {
+            if self.is_selectable(idx) {
                 self.list_state.select(Some(idx));
+                self.commit_scroll = 0;
                 return;
             }
         }
     }
 
     pub fn swap_down(&mut self) {
-        let lines = self.todo.lines();
-        let len = lines.len();
+        if self.anchor.is_some() {
+            return self.move_selection_down();
+        }
+
+        let len = self.todo.len();
         if len == 0 {
             return;
         }
@@ -103,17 +371,23 @@ impl RebaseEditor {
         let mut idx = current_line;
         for _ in 0..len {
             idx = (idx + 1) % len;
-            if !matches!(lines[idx], RebaseTodoLine::Comment { .. }) {
-                self.todo.lines_mut().swap(current_line, idx);
-                self.list_state.select(Some(idx));
+            if self.is_selectable(idx) {
+                let label = format!("move {} down", self.describe_current_line());
+                self.apply(label, |editor| {
+                    editor.todo.swap(current_line, idx);
+                    editor.list_state.select(Some(idx));
+                });
                 return;
             }
         }
     }
 
     pub fn swap_up(&mut self) {
-        let lines = self.todo.lines();
-        let len = lines.len();
+        if self.anchor.is_some() {
+            return self.move_selection_up();
+        }
+
+        let len = self.todo.len();
         if len == 0 {
             return;
         }
@@ -126,21 +400,342 @@ impl RebaseEditor {
             } else {
                 idx -= 1;
             }
-            if !matches!(lines[idx], RebaseTodoLine::Comment { .. }) {
-                self.todo.lines_mut().swap(current_line, idx);
-                self.list_state.select(Some(idx));
+            if self.is_selectable(idx) {
+                let label = format!("move {} up", self.describe_current_line());
+                self.apply(label, |editor| {
+                    editor.todo.swap(current_line, idx);
+                    editor.list_state.select(Some(idx));
+                });
                 return;
             }
         }
     }
 
-    pub fn set_current_line(&mut self, line: RebaseTodoLine) {
+    /// Move the visual selection down as a block by moving the next line
+    /// above it. Unlike single lines, blocks do not wrap around.
+    fn move_selection_down(&mut self) {
+        let (start, end) = self.selection().into_inner();
+        let Some(next) = (end + 1..self.todo.len()).find(|&idx| self.is_selectable(idx)) else {
+            return;
+        };
+
+        let label = format!("move {} lines down", end - start + 1);
+        self.apply(label, |editor| {
+            editor.todo.move_line(next, start);
+            editor.list_state.select(Some(editor.selected() + 1));
+            editor.anchor = editor.anchor.map(|anchor| anchor + 1);
+        });
+    }
+
+    /// Move the visual selection up as a block by moving the previous line
+    /// below it.
+    fn move_selection_up(&mut self) {
+        let (start, end) = self.selection().into_inner();
+        let Some(previous) = (0..start).rev().find(|&idx| self.is_selectable(idx)) else {
+            return;
+        };
+
+        let label = format!("move {} lines up", end - start + 1);
+        self.apply(label, |editor| {
+            editor.todo.move_line(previous, end);
+            editor.list_state.select(Some(editor.selected() - 1));
+            editor.anchor = editor.anchor.map(|anchor| anchor - 1);
+        });
+    }
+
+    /// Move the selected commit to the tip of the previous or next lane of
+    /// the graph.
+    pub fn move_to_lane(&mut self, forward: bool) {
         let idx = self.selected();
-        self.todo.lines_mut()[idx] = line;
+        if self
+            .get_current_line()
+            .and_then(|l| l.get_commit())
+            .is_none()
+        {
+            return;
+        }
+
+        let graph = TodoGraph::new(&self.todo);
+        let Some(lane) = graph.lane_of(idx) else {
+            return;
+        };
+        let target = if forward {
+            (lane + 1..graph.lanes.len()).find(|&l| graph.accepts_commits(&self.todo, l))
+        } else {
+            (0..lane)
+                .rev()
+                .find(|&l| graph.accepts_commits(&self.todo, l))
+        };
+        let Some(target) = target else {
+            return;
+        };
+
+        // Moving a line down shifts the insertion point up by one
+        let to = graph.insertion_point(&self.todo, target);
+        let to = if idx < to { to - 1 } else { to };
+
+        let label = match &graph.lanes[target].label {
+            Some(name) => format!("move {} to {}", self.describe_current_line(), name),
+            None => format!(
+                "move {} to lane {}",
+                self.describe_current_line(),
+                target + 1
+            ),
+        };
+        self.anchor = None;
+        self.apply(label, |editor| {
+            editor.todo.move_line(idx, to);
+            editor.list_state.select(Some(to));
+        });
+    }
+
+    /// Replace every commit line in the selection with the line built from
+    /// its commit and subject, as a single change, and end the selection.
+    pub fn set_selected_lines(&mut self, build: impl Fn(String, Vec<String>) -> RebaseTodoLine) {
+        let lines = self.selection().collect::<Vec<_>>();
+        self.set_lines(lines, build);
+    }
+
+    /// The commit lines a squash or fixup changes. A selection of several
+    /// commits melds into its first one, which is left as it is, while a
+    /// single commit melds into the commit above it.
+    fn meld_lines(&self) -> Vec<usize> {
+        let mut lines = self
+            .selection()
+            .filter(|&idx| {
+                self.todo
+                    .get(idx)
+                    .is_some_and(|line| line.get_commit().is_some())
+            })
+            .collect::<Vec<_>>();
+        if lines.len() > 1 {
+            lines.remove(0);
+        }
+        lines
+    }
+
+    /// Squash or fixup the selection, as a single change.
+    pub fn meld_selected_lines(&mut self, build: impl Fn(String, Vec<String>) -> RebaseTodoLine) {
+        let lines = self.meld_lines();
+        self.set_lines(lines, build);
+    }
+
+    fn set_lines(
+        &mut self,
+        lines: Vec<usize>,
+        build: impl Fn(String, Vec<String>) -> RebaseTodoLine,
+    ) {
+        let lines = lines
+            .into_iter()
+            .filter_map(|idx| {
+                let line = self.todo.get(idx)?;
+                let commit = line.get_commit()?.to_string();
+                let rest = line.get_rest().unwrap_or_default().to_vec();
+                Some((idx, build(commit, rest)))
+            })
+            .collect::<Vec<_>>();
+        self.anchor = None;
+
+        let label = match lines.as_slice() {
+            [] => return,
+            [(_, line)] => format!(
+                "{} {}",
+                line.get_action(),
+                line.get_commit().unwrap_or_default()
+            ),
+            [(_, first), ..] => format!("{} {} commits", first.get_action(), lines.len()),
+        };
+        self.apply(label, |editor| {
+            for (idx, line) in lines {
+                editor.todo.set(idx, line);
+            }
+        });
+    }
+
+    /// Insert a line after the selected one and select it.
+    pub fn insert_after_current_line(&mut self, line: RebaseTodoLine) {
+        self.anchor = None;
+        let idx = (self.selected() + 1).min(self.todo.len());
+        let label = format!("insert {}", line.get_action());
+        self.apply(label, |editor| {
+            editor.todo.insert(idx, line);
+            editor.list_state.select(Some(idx));
+        });
+    }
+
+    /// Remove the lines in the selection. Deleted commits are checked
+    /// against `rebase.missingCommitsCheck` on save.
+    pub fn delete_selected_lines(&mut self) {
+        let lines = self
+            .selection()
+            .filter(|&idx| self.is_selectable(idx))
+            .collect::<Vec<_>>();
+        let Some(&first) = lines.first() else {
+            return;
+        };
+        self.anchor = None;
+
+        let label = if lines.len() == 1 {
+            self.list_state.select(Some(first));
+            format!("delete {}", self.describe_current_line())
+        } else {
+            format!("delete {} lines", lines.len())
+        };
+        self.apply(label, |editor| {
+            for &idx in lines.iter().rev() {
+                editor.todo.remove(idx);
+            }
+            editor
+                .list_state
+                .select(Some(first.min(editor.todo.len().saturating_sub(1))));
+            if !editor.is_selectable(editor.selected()) {
+                editor.move_cursor_up();
+            }
+        });
+    }
+
+    pub fn open_exec_input(&mut self, target: ExecTarget) {
+        let text = match (target, self.get_current_line()) {
+            (ExecTarget::Edit(_), Some(RebaseTodoLine::Exec { command })) => command,
+            _ => "",
+        };
+
+        let mut textarea = TextArea::single_line(text);
+        textarea.move_to_end();
+        self.exec_input = Some(ExecInput { target, textarea });
+    }
+
+    /// Write the exec input into the todo. An empty command is ignored.
+    pub fn confirm_exec_input(&mut self) {
+        let Some(input) = self.exec_input.take() else {
+            return;
+        };
+
+        let command = input.textarea.text().trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+
+        match input.target {
+            ExecTarget::Insert => self.insert_after_current_line(RebaseTodoLine::Exec { command }),
+            ExecTarget::Edit(idx) => self.apply("edit exec", |editor| {
+                editor.todo.set(idx, RebaseTodoLine::Exec { command })
+            }),
+            ExecTarget::EveryCommit => self.apply("exec after every commit", |editor| {
+                editor.todo.insert_exec_after_commits(&command);
+            }),
+        }
+    }
+
+    /// Rewrite the selected commit's message in the right pane, starting
+    /// from a previous rewrite or the commit's own message.
+    pub fn open_reword_input(&mut self) {
+        let Some(line) = self.get_current_line() else {
+            return;
+        };
+        let Some(commit) = line.get_commit().map(str::to_string) else {
+            return;
+        };
+
+        let message = match self.rewords.get(&commit) {
+            Some(message) => message.clone(),
+            None => {
+                let original = self
+                    .get_commit_for_line(line)
+                    .and_then(|c| c.message().map(str::to_string))
+                    .unwrap_or_default();
+                CommitMessage::parse(&original, &self.comment)
+            }
+        };
+
+        let input = MessageInput::new(&message);
+        self.reword_input = Some((commit, message, input));
+    }
+
+    pub fn confirm_reword_input(&mut self) {
+        if let Some((commit, mut message, input)) = self.reword_input.take() {
+            input.apply(&mut message);
+            self.apply(format!("reword message {}", commit), |editor| {
+                editor.rewords.insert(commit, message);
+            });
+        }
+    }
+
+    /// The selectable todo line drawn at a screen position.
+    fn line_at(&self, position: Position) -> Option<usize> {
+        let inner = Block::default().borders(Borders::ALL).inner(self.todo_area);
+        if !inner.contains(position) {
+            return None;
+        }
+
+        let idx = (position.y - inner.y) as usize + self.list_state.offset();
+        (idx < self.todo.len() && self.is_selectable(idx)).then_some(idx)
+    }
+
+    /// Handle a mouse event, returning the key to press when an instruction
+    /// is clicked.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<KeyEvent> {
+        let position = Position::new(mouse.column, mouse.row);
+
+        let clicked = self.instruction_targets.iter().find(|(area, _)| {
+            mouse.kind == MouseEventKind::Down(MouseButton::Left) && area.contains(position)
+        });
+        if let Some((_, key)) = clicked {
+            return Some(*key);
+        }
+
+        if self.exec_input.is_some() || self.reword_input.is_some() {
+            return None;
+        }
+
+        match mouse.kind {
+            // Click to select, shift-click to extend the selection
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(idx) = self.line_at(position) {
+                    if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                        self.anchor.get_or_insert(self.selected());
+                    } else {
+                        self.anchor = None;
+                        self.drag = Some(self.snapshot());
+                    }
+                    self.list_state.select(Some(idx));
+                    self.commit_scroll = 0;
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let (Some(_), Some(idx)) = (&self.drag, self.line_at(position)) {
+                    self.todo.move_line(self.selected(), idx);
+                    self.list_state.select(Some(idx));
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some(before) = self.drag.take() {
+                    let label = format!(
+                        "move {} to line {}",
+                        self.describe_current_line(),
+                        self.selected() + 1
+                    );
+                    self.record(label, before);
+                }
+            }
+            MouseEventKind::ScrollDown if self.todo_area.contains(position) => {
+                self.move_cursor_down()
+            }
+            MouseEventKind::ScrollUp if self.todo_area.contains(position) => self.move_cursor_up(),
+            MouseEventKind::ScrollDown if self.commit_area.contains(position) => {
+                self.commit_scroll = self.commit_scroll.saturating_add(3)
+            }
+            MouseEventKind::ScrollUp if self.commit_area.contains(position) => {
+                self.commit_scroll = self.commit_scroll.saturating_sub(3)
+            }
+            _ => {}
+        }
+
+        None
     }
 
     pub fn get_current_line(&self) -> Option<&RebaseTodoLine> {
-        self.todo.lines().get(self.selected())
+        self.todo.get(self.selected())
     }
 
     pub fn get_commit_for_line(&self, line: &RebaseTodoLine) -> Option<Commit<'_>> {
@@ -152,15 +747,143 @@ impl RebaseEditor {
             .and_then(|r| r.into_commit().ok())
     }
 
-    pub fn save(&self) -> Result<(), color_eyre::Report> {
-        let content = self
-            .todo
-            .lines()
+    /// Send the todo to the simulator if it changed, and collect finished
+    /// runs, waiting up to `wait` for the current one.
+    fn simulate(&mut self, wait: Duration) {
+        let Some(simulator) = &self.simulator else {
+            return;
+        };
+
+        let content = self.todo.to_string();
+        if self.requested.as_ref() != Some(&content) {
+            simulator.request(content.clone());
+            self.requested = Some(content);
+        }
+
+        while self.simulation_pending() {
+            let Some(report) = self
+                .simulator
+                .as_ref()
+                .and_then(|simulator| simulator.next_report(wait))
+            else {
+                break;
+            };
+            self.simulation = Some(report);
+        }
+    }
+
+    /// Whether the simulator has yet to finish with the current todo.
+    fn simulation_pending(&self) -> bool {
+        self.requested.is_some()
+            && self.simulation.as_ref().map(|(content, _)| content) != self.requested.as_ref()
+    }
+
+    /// The simulation of the current todo, once it has finished.
+    fn report(&self) -> Option<&Report> {
+        match &self.simulation {
+            Some((content, report)) if Some(content) == self.requested.as_ref() => report.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn toggle_compare_view(&mut self) {
+        self.compare_view = !self.compare_view;
+        self.commit_scroll = 0;
+    }
+
+    /// Paths that would conflict when applying a line.
+    fn conflicts_at(&self, idx: usize) -> Option<&[String]> {
+        let report = self.report()?;
+        report.simulation.conflicts.get(&idx).map(Vec::as_slice)
+    }
+
+    pub fn problems(&self) -> Vec<Problem> {
+        validate(&self.todo, &self.comment, |name| {
+            self.repo.revparse_single(name).is_ok()
+        })
+    }
+
+    /// Original commit lines that were deleted, when git checks for them.
+    fn missing_commits(&self) -> Vec<&RebaseTodoLine> {
+        match self.missing_check {
+            MissingCommitsCheck::Ignore => vec![],
+            _ => missing_commits(&self.original_commits, &self.todo),
+        }
+    }
+
+    /// Add a drop line for every missing commit after the last command, so
+    /// git sees they were removed on purpose.
+    pub fn drop_missing_commits(&mut self) {
+        let drops = self
+            .missing_commits()
+            .into_iter()
+            .map(|line| RebaseTodoLine::Drop {
+                commit: line.get_commit().unwrap_or_default().to_string(),
+                rest: line.get_rest().unwrap_or_default().to_vec(),
+            })
+            .collect::<Vec<_>>();
+        let idx = (0..self.todo.len())
+            .rev()
+            .find(|&idx| !matches!(self.todo.get(idx), Some(RebaseTodoLine::Comment { .. })))
+            .map_or(0, |idx| idx + 1);
+
+        let label = format!("drop {} missing commits", drops.len());
+        self.apply(label, |editor| {
+            for (i, line) in drops.into_iter().enumerate() {
+                editor.todo.insert(idx + i, line);
+            }
+        });
+    }
+
+    fn has_errors(problems: &[Problem]) -> bool {
+        problems
             .iter()
-            .map(|line| line.to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
-        std::fs::write(&self.path, content)?;
+            .any(|problem| problem.severity == Severity::Error)
+    }
+
+    /// Save unless the todo has errors, or deleted commits to decide on
+    /// first. Returns whether the todo was saved.
+    pub fn try_save(&mut self) -> Result<bool, color_eyre::Report> {
+        if Self::has_errors(&self.problems()) {
+            self.save_blocked = true;
+        } else if !self.missing_commits().is_empty() {
+            self.missing_prompt = true;
+        } else {
+            self.save()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    pub fn save(&self) -> Result<(), color_eyre::Report> {
+        let mut todo = self.todo.clone();
+
+        // Messages written up front are stored next to the todo, and the
+        // reword becomes a pick that amends the message from that file
+        let state_dir = self
+            .path
+            .parent()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?;
+        for (commit, message) in &self.rewords {
+            let file = state_dir.join(format!("glitt-reword-{}", commit));
+            let command = format!(
+                "git commit --amend --quiet -F {}",
+                quote(&file.to_string_lossy())
+            );
+            if todo.reword_with_exec(commit, &command) {
+                std::fs::write(&file, format!("{}\n", message.text()))?;
+            }
+        }
+
+        if self.mode != TodoMode::Rebase {
+            for idx in (0..todo.len()).rev() {
+                if matches!(todo.get(idx), Some(RebaseTodoLine::Drop { .. })) {
+                    todo.remove(idx);
+                }
+            }
+        }
+
+        std::fs::write(&self.path, todo.to_string())?;
         Ok(())
     }
 
@@ -169,23 +892,69 @@ impl RebaseEditor {
         Ok(())
     }
 
-    pub fn render_todo_list(&mut self, frame: &mut ratatui::Frame, area: Rect) {
-        let block = Block::default().title("Todo").borders(Borders::ALL);
+    pub fn render_todo_list(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        graph: Option<&TodoGraph>,
+    ) {
+        let mut title = match (self.anchor, graph) {
+            (Some(_), _) => format!("Todo ({} selected)", self.selection().count()),
+            (None, Some(_)) => "Todo (graph)".to_string(),
+            (None, None) => "Todo".to_string(),
+        };
+        let conflicts = (0..self.todo.len())
+            .filter(|&idx| self.conflicts_at(idx).is_some())
+            .count();
+        if self.simulation_pending() {
+            title.push_str(" simulating…");
+        } else if conflicts > 0 {
+            title.push_str(&format!(" {} would conflict", conflicts));
+        }
+        let block = Block::default().title(title).borders(Borders::ALL);
         let selected = self.selected();
+        let selection = self.selection();
 
         let items: Vec<ListItem> = self
             .todo
             .lines()
-            .iter()
             .enumerate()
             .map(|(i, line)| {
                 let style = if i == selected {
                     line.get_selected_style()
+                } else if selection.contains(&i) {
+                    line.get_selected_style().add_modifier(Modifier::DIM)
+                } else if i < self.locked {
+                    line.get_style().add_modifier(Modifier::DIM)
                 } else {
                     line.get_style()
                 };
 
-                ListItem::new(Line::from(line.to_string())).style(style)
+                let text = match line {
+                    RebaseTodoLine::Reword { commit, .. } => match self.rewords.get(commit) {
+                        Some(message) => format!("reword {} {}", commit, message.subject),
+                        None => line.to_string(),
+                    },
+                    _ => line.to_string(),
+                };
+
+                let mut spans = vec![];
+                if let Some(graph) = graph {
+                    spans.push(Span::from(graph.prefix(i, line)));
+                }
+                // Mark lines that would conflict, keeping the rest aligned
+                if conflicts > 0 {
+                    spans.push(match self.conflicts_at(i) {
+                        Some(_) => Span::styled("✗ ", Style::default().fg(Color::Red).bold()),
+                        None => Span::from("  "),
+                    });
+                }
+                spans.push(Span::styled(text, style));
+
+                match graph {
+                    Some(_) => ListItem::new(Line::from(spans)),
+                    None => ListItem::new(Line::from(spans)).style(style),
+                }
             })
             .collect();
 
@@ -196,73 +965,171 @@ impl RebaseEditor {
             .orientation(ScrollbarOrientation::VerticalRight)
             .style(Style::default().fg(Color::Gray));
 
-        let mut scroll_state = ScrollbarState::new(self.todo.lines().len()).position(selected);
+        let mut scroll_state = ScrollbarState::new(self.todo.len()).position(selected);
         frame.render_stateful_widget(scrollbar, area, &mut scroll_state);
     }
 
-    fn normalize_diff_line(line: &str) -> String {
-        line.replace('\t', "  ")
+    pub fn render_history(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("History").borders(Borders::ALL);
+
+        let applied = self.history.applied().collect::<Vec<_>>();
+        let mut lines = applied
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let style = if i + 1 == applied.len() {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Line::from(label.to_string()).style(style)
+            })
+            .collect::<Vec<_>>();
+        lines.extend(self.history.undone().map(|label| {
+            Line::from(label.to_string()).style(Style::default().add_modifier(Modifier::DIM))
+        }));
+
+        // Keep the most recent applied change in view
+        let height = block.inner(area).height as usize;
+        let scroll = applied.len().saturating_sub(height);
+        let paragraph = Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, area);
     }
 
-    fn get_commit_diff(&self, commit: &git2::Commit) -> Option<Vec<Line<'_>>> {
-        let tree = commit.tree().ok()?;
-        let parent = commit.parent(0).ok()?;
-        let parent_tree = parent.tree().ok()?;
+    pub fn render_problems(&self, frame: &mut ratatui::Frame, area: Rect, problems: &[Problem]) {
+        let blocked = self.save_blocked
+            && problems
+                .iter()
+                .any(|problem| problem.severity == Severity::Error);
+        let block = if blocked {
+            Block::default()
+                .title("Problems: fix the errors or press Q to save anyway")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+        } else {
+            Block::default().title("Problems").borders(Borders::ALL)
+        };
+
+        let lines = problems
+            .iter()
+            .map(|problem| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{} on line {}",
+                            problem.severity.get_label(),
+                            problem.line + 1
+                        ),
+                        problem.severity.get_style().bold(),
+                    ),
+                    Span::from(format!(": {}", problem.message)),
+                ])
+            })
+            .collect::<Vec<_>>();
 
-        let diff = self
-            .repo
-            .diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)
-            .ok()?;
+        let paragraph = Paragraph::new(lines).block(block);
+        frame.render_widget(paragraph, area);
+    }
 
-        let mut diffs = vec![];
-        diff.print(DiffFormat::Patch, |_, _, line| {
-            let style = match line.origin() {
-                '+' => Style::default().fg(Color::Green),
-                '-' => Style::default().fg(Color::Red),
-                _ => Style::default(),
+    /// The diff from the original branch tip to the simulated result.
+    pub fn render_comparison(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let report = self.report();
+        let Some(comparison) = report.and_then(|report| report.comparison.as_ref()) else {
+            let block = Block::default()
+                .title("Result vs original")
+                .borders(Borders::ALL);
+            let message = if self.simulation_pending() {
+                "Simulating the todo…"
+            } else {
+                "The todo could not be simulated"
             };
+            let paragraph = Paragraph::new(message).block(block);
+            frame.render_widget(paragraph, area);
+            return;
+        };
 
-            let chunk = str::from_utf8(line.content()).unwrap_or_default();
-            for line in chunk.lines() {
-                let line = Line::from(Self::normalize_diff_line(line))
-                    .style(style)
-                    .alignment(Alignment::Left);
-                diffs.push(line);
-            }
-            true
-        })
-        .ok()?;
+        let conflicted = report.is_some_and(|report| !report.simulation.conflicts.is_empty());
+        let mut title = match comparison.files {
+            0 => "Result vs original: identical".to_string(),
+            1 => "Result vs original: 1 file differs".to_string(),
+            n => format!("Result vs original: {} files differ", n),
+        };
+        if conflicted {
+            title.push_str(" (taking each commit's side of conflicts)");
+        }
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(match comparison.files {
+                0 => Style::default().fg(Color::Green),
+                _ => Style::default().fg(Color::Yellow),
+            });
+
+        let lines = match comparison.files {
+            0 => vec![Line::from(
+                "The rebase ends with the same tree as the original branch",
+            )],
+            _ => comparison.lines.clone(),
+        };
+        let paragraph = Paragraph::new(lines)
+            .scroll((self.commit_scroll, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn render_exec(&self, frame: &mut ratatui::Frame, area: Rect, command: &str) {
+        let block = Block::default().title("Exec").borders(Borders::ALL);
+        let paragraph = Paragraph::new(highlight(command))
+            .wrap(Wrap { trim: false })
+            .block(block);
 
-        Some(diffs)
+        frame.render_widget(paragraph, area);
     }
 
-    pub fn format_commit(&self, commit: &git2::Commit) -> Paragraph<'_> {
-        let timestamp = DateTime::from_timestamp(commit.time().seconds(), 0)
-            .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+    pub fn render_commit_info(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        if let Some((_, _, input)) = &mut self.reword_input {
+            input.render(frame, area, true);
+            return;
+        }
 
-        let diff = self.get_commit_diff(commit).unwrap_or_default();
+        if self.compare_view {
+            self.render_comparison(frame, area);
+            return;
+        }
 
-        let mut content = vec![];
-        content.push(
-            format!(
-                "Author: {} <{}>\n",
-                commit.author().name().unwrap_or("Unknown"),
-                commit.author().email().unwrap_or("unknown")
-            )
-            .into(),
-        );
-        content.push(format!("Date:   {}\n\n", timestamp).into());
-        content.push("".into());
-        content.push(format!("{}\n\n", commit.message().unwrap_or("No commit message")).into());
-        content.push("".into());
+        let area = match self.conflicts_at(self.selected()) {
+            Some(paths) => {
+                let layout = Layout::vertical([
+                    Constraint::Length(paths.len().min(6) as u16 + 2),
+                    Constraint::Min(1),
+                ])
+                .split(area);
 
-        content.extend(diff);
+                let block = Block::default()
+                    .title("Would conflict")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Red));
+                let lines = paths
+                    .iter()
+                    .map(|path| Line::from(path.as_str()))
+                    .collect::<Vec<_>>();
+                frame.render_widget(Paragraph::new(lines).block(block), layout[0]);
 
-        Paragraph::new(content).style(Style::default())
-    }
+                layout[1]
+            }
+            None => area,
+        };
 
-    pub fn render_commit_info(&self, frame: &mut ratatui::Frame, area: Rect) {
         let line = self.get_current_line();
+        if let Some(RebaseTodoLine::Exec { command }) = line {
+            self.render_exec(frame, area, command);
+            return;
+        }
+
         let commit = line.and_then(|l| self.get_commit_for_line(l));
 
         let commit = match commit {
@@ -276,26 +1143,154 @@ impl RebaseEditor {
         };
 
         let block = Block::default().title("Commit").borders(Borders::ALL);
-        let paragraph = self.format_commit(&commit).block(block);
+        let paragraph = format_commit(&self.repo, &commit)
+            .scroll((self.commit_scroll, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn render_missing_prompt(&self, frame: &mut ratatui::Frame, area: Rect) {
+        if !self.missing_prompt {
+            return;
+        }
+
+        let severity = self.missing_check.severity().unwrap_or(Severity::Warning);
+        let title = match severity {
+            Severity::Error => "Missing commits: git will refuse to continue",
+            Severity::Warning => "Missing commits: git will warn about these",
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(severity.get_style());
+
+        let lines = self
+            .missing_commits()
+            .into_iter()
+            .map(|line| Line::from(line.to_string()).style(line.get_style()))
+            .collect::<Vec<_>>();
 
+        let paragraph = Paragraph::new(lines).block(block);
         frame.render_widget(paragraph, area);
     }
 
-    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
-        let instructions = Paragraph::new(format!(
-            "{} Move  {} pick  {} edit  {} reword {} squash  {} fixup  {} drop  {} quit and save  {} abort",
-            "↑/↓".bold(),
-            "p".bold(),
-            "e".bold(),
-            "r".bold(),
-            "s".bold(),
-            "f".bold(),
-            "d".bold(),
-            "q".bold(),
-            "a".bold()
-        ))
-        .style(Style::default());
+    pub fn render_exec_input(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let Some(input) = &mut self.exec_input else {
+            return;
+        };
+
+        let title = match input.target {
+            ExecTarget::Insert => "Insert exec",
+            ExecTarget::Edit(_) => "Edit exec",
+            ExecTarget::EveryCommit => "Exec after every commit",
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Blue));
+
+        input.textarea.render(frame, area, block, true);
+    }
+
+    fn instructions(&self, problems: &[Problem]) -> Vec<Instruction> {
+        if self.reword_input.is_some() {
+            return vec![
+                ("Tab", "Switch field", key(KeyCode::Tab)),
+                ("^s", "keep message", ctrl('s')),
+                ("Esc", "cancel", key(KeyCode::Esc)),
+            ];
+        }
+
+        if self.missing_prompt {
+            return vec![
+                ("d", "drop them and save", key(KeyCode::Char('d'))),
+                ("Q", "save anyway", key(KeyCode::Char('Q'))),
+                ("Esc", "keep editing", key(KeyCode::Esc)),
+            ];
+        }
+
+        if self.exec_input.is_some() {
+            return vec![
+                ("Enter", "confirm", key(KeyCode::Enter)),
+                ("Esc", "cancel", key(KeyCode::Esc)),
+            ];
+        }
+
+        if let TodoMode::Sequencer { .. } = self.mode {
+            let mut instructions = vec![
+                ("↑/↓", "Move", None),
+                ("v", "select", key(KeyCode::Char('v'))),
+                ("p", "keep", key(KeyCode::Char('p'))),
+                ("d", "drop", key(KeyCode::Char('d'))),
+                ("q", "quit and save", key(KeyCode::Char('q'))),
+                ("a", "abort", key(KeyCode::Char('a'))),
+            ];
+            if Self::has_errors(problems) {
+                instructions.push(("Q", "save anyway", key(KeyCode::Char('Q'))));
+            }
+            return instructions;
+        }
+
+        let mut instructions = vec![
+            ("↑/↓", "Move", None),
+            ("v", "select", key(KeyCode::Char('v'))),
+            ("g", "graph", key(KeyCode::Char('g'))),
+            ("c", "compare result", key(KeyCode::Char('c'))),
+        ];
+        if self.graph_view {
+            instructions.push(("⇧←/→", "change branch", None));
+        }
+        instructions.extend([
+            ("p", "pick", key(KeyCode::Char('p'))),
+            ("e", "edit", key(KeyCode::Char('e'))),
+            ("r", "reword", key(KeyCode::Char('r'))),
+            ("s", "squash", key(KeyCode::Char('s'))),
+            ("f", "fixup (-C/-c)", key(KeyCode::Char('f'))),
+            ("d", "drop", key(KeyCode::Char('d'))),
+            ("b", "break", key(KeyCode::Char('b'))),
+            ("n", "noop", key(KeyCode::Char('n'))),
+            ("x", "exec", key(KeyCode::Char('x'))),
+            ("X", "exec all", key(KeyCode::Char('X'))),
+            ("Enter", "edit exec/message", key(KeyCode::Enter)),
+            ("D", "delete", key(KeyCode::Char('D'))),
+            ("u", "undo", key(KeyCode::Char('u'))),
+            ("^r", "redo", ctrl('r')),
+            ("q", "quit and save", key(KeyCode::Char('q'))),
+            ("a", "abort", key(KeyCode::Char('a'))),
+        ]);
+        if Self::has_errors(problems) {
+            instructions.push(("Q", "save anyway", key(KeyCode::Char('Q'))));
+        }
+        instructions
+    }
+
+    /// Draw the instruction bar, remembering where each entry is so it can
+    /// be clicked.
+    pub fn render_instructions(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        problems: &[Problem],
+    ) {
+        let mut spans = vec![];
+        let mut x = area.x;
+        self.instruction_targets.clear();
+
+        for (label, action, key) in self.instructions(problems) {
+            let entry = [Span::from(label).bold(), Span::from(format!(" {}", action))];
+            let width = entry.iter().map(|span| span.width() as u16).sum::<u16>();
+            if let Some(key) = key {
+                let target = Rect::new(x, area.y, width, 1).intersection(area);
+                self.instruction_targets.push((target, key));
+            }
 
+            spans.extend(entry);
+            spans.push(Span::from("  "));
+            x = x.saturating_add(width + 2);
+        }
+
+        let instructions = Paragraph::new(Line::from(spans)).style(Style::default());
         frame.render_widget(instructions, area);
     }
 }
@@ -306,26 +1301,211 @@ impl Editor for RebaseEditor {
     }
 
     fn render(&mut self, frame: &mut ratatui::Frame) {
+        // Short todos finish before drawing, longer ones are drawn as
+        // pending and redrawn once they finish
+        self.simulate(Duration::from_millis(20));
+
+        // Validation resolves every commit and label, so it runs once a frame
+        let problems = self.problems();
+
         let main_area =
             Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
 
-        self.render_instructions(frame, main_area[0]);
+        self.render_instructions(frame, main_area[0], &problems);
+
+        let input_height = if self.exec_input.is_some() {
+            3
+        } else if self.missing_prompt {
+            self.missing_commits().len().min(8) as u16 + 2
+        } else {
+            0
+        };
+        let body_area = Layout::vertical([Constraint::Min(1), Constraint::Length(input_height)])
+            .split(main_area[1]);
+        self.render_exec_input(frame, body_area[1]);
+        self.render_missing_prompt(frame, body_area[1]);
+
+        let graph = self
+            .graph_view
+            .then(|| TodoGraph::new(&self.todo))
+            .filter(|graph| graph.is_branched());
+        let graph_width = graph.as_ref().map_or(0, TodoGraph::width);
+
+        let editor_area = Layout::horizontal([
+            Constraint::Max(36 + 2 * graph_width as u16),
+            Constraint::Fill(1),
+        ])
+        .split(body_area[0]);
 
-        let editor_area =
-            Layout::horizontal([Constraint::Max(36), Constraint::Fill(1)]).split(main_area[1]);
+        let todo_area =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(7)]).split(editor_area[0]);
 
-        self.render_todo_list(frame, editor_area[0]);
-        self.render_commit_info(frame, editor_area[1]);
+        self.todo_area = todo_area[0];
+
+        self.render_todo_list(frame, todo_area[0], graph.as_ref());
+        self.render_history(frame, todo_area[1]);
+
+        let problems_height = match problems.len() {
+            0 => 0,
+            n => n.min(6) as u16 + 2,
+        };
+        let info_area = Layout::vertical([Constraint::Min(1), Constraint::Length(problems_height)])
+            .split(editor_area[1]);
+
+        self.commit_area = info_area[0];
+        self.render_commit_info(frame, info_area[0]);
+        self.render_problems(frame, info_area[1], &problems);
     }
 
     fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        execute!(std::io::stdout(), EnableMouseCapture)?;
+        let result = self.handle_events(&mut terminal);
+        execute!(std::io::stdout(), DisableMouseCapture)?;
+        result
+    }
+}
+
+impl RebaseEditor {
+    fn handle_events(&mut self, terminal: &mut DefaultTerminal) -> color_eyre::Result<()> {
         terminal.clear()?;
         loop {
             terminal.draw(|frame| self.render(frame))?;
+            if self.simulation_pending() && !event::poll(Duration::from_millis(50))? {
+                continue;
+            }
+            let event = match event::read()? {
+                Event::Mouse(mouse) => match self.handle_mouse(mouse) {
+                    Some(key) => Event::Key(key),
+                    None => continue,
+                },
+                event => event,
+            };
+
+            if let Some(input) = &mut self.exec_input {
+                match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }) => self.confirm_exec_input(),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Esc, ..
+                    }) => self.exec_input = None,
+                    Event::Key(key) => {
+                        input.textarea.handle_key(key);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if let Some((_, _, input)) = &mut self.reword_input {
+                match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('s'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) => self.confirm_reword_input(),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Esc, ..
+                    }) => self.reword_input = None,
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Tab | KeyCode::BackTab,
+                        ..
+                    }) => input.toggle_focus(),
+                    Event::Key(key) => {
+                        input.handle_key(key);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if self.missing_prompt {
+                match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(c @ ('d' | 'Q')),
+                        ..
+                    }) => {
+                        if c == 'd' {
+                            self.drop_missing_commits();
+                        }
+                        self.save()?;
+                        terminal.clear()?;
+                        return Ok(());
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Esc, ..
+                    }) => self.missing_prompt = false,
+                    _ => {}
+                }
+                continue;
+            }
+
             let line = self.get_current_line();
             let commit = line.and_then(|l| l.get_commit());
+            let mode = self.mode;
+
+            match (event, commit) {
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('v'),
+                        ..
+                    }),
+                    _,
+                ) => self.toggle_visual(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Esc, ..
+                    }),
+                    _,
+                ) => self.anchor = None,
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('g'),
+                        ..
+                    }),
+                    _,
+                ) if self.mode == TodoMode::Rebase => self.graph_view = !self.graph_view,
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('c'),
+                        ..
+                    }),
+                    _,
+                ) => self.toggle_compare_view(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Left,
+                        modifiers: KeyModifiers::SHIFT,
+                        ..
+                    }),
+                    _,
+                ) if self.graph_view => self.move_to_lane(false),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Right,
+                        modifiers: KeyModifiers::SHIFT,
+                        ..
+                    }),
+                    _,
+                ) if self.graph_view => self.move_to_lane(true),
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('u'),
+                        ..
+                    }),
+                    _,
+                ) => self.undo(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('r'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }),
+                    _,
+                ) => self.redo(),
 
-            match (event::read()?, commit) {
                 (
                     Event::Key(KeyEvent {
                         code: KeyCode::Down,
@@ -361,27 +1541,17 @@ impl Editor for RebaseEditor {
                         code: KeyCode::Char('p'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Pick {
-                        commit: commit.to_string(),
-                        rest: rest,
-                    });
-                }
+                    _,
+                ) => self.set_selected_lines(|commit, rest| mode.keep_line(commit, rest)),
 
                 (
                     Event::Key(KeyEvent {
                         code: KeyCode::Char('e'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Edit {
-                        commit: commit.to_string(),
-                        rest,
-                    });
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    self.set_selected_lines(|commit, rest| RebaseTodoLine::Edit { commit, rest })
                 }
 
                 (
@@ -389,13 +1559,14 @@ impl Editor for RebaseEditor {
                         code: KeyCode::Char('r'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Reword {
-                        commit: commit.to_string(),
-                        rest,
-                    });
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    // Only a single reword opens the message straight away
+                    let single = self.anchor.is_none() && commit.is_some();
+                    self.set_selected_lines(|commit, rest| RebaseTodoLine::Reword { commit, rest });
+                    if single {
+                        self.open_reword_input();
+                    }
                 }
 
                 (
@@ -403,13 +1574,9 @@ impl Editor for RebaseEditor {
                         code: KeyCode::Char('s'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Squash {
-                        commit: commit.to_string(),
-                        rest,
-                    });
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    self.meld_selected_lines(|commit, rest| RebaseTodoLine::Squash { commit, rest })
                 }
 
                 (
@@ -417,11 +1584,29 @@ impl Editor for RebaseEditor {
                         code: KeyCode::Char('f'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Fixup {
-                        commit: commit.to_string(),
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    // Cycle fixup → fixup -C → fixup -c → fixup, following
+                    // the first commit that changes
+                    let line = self
+                        .meld_lines()
+                        .first()
+                        .and_then(|&idx| self.todo.get(idx));
+                    let (use_message, edit_message) = match line {
+                        Some(RebaseTodoLine::Fixup {
+                            use_message: false,
+                            edit_message: false,
+                            ..
+                        }) => (true, false),
+                        Some(RebaseTodoLine::Fixup {
+                            use_message: true, ..
+                        }) => (false, true),
+                        _ => (false, false),
+                    };
+                    self.meld_selected_lines(|commit, rest| RebaseTodoLine::Fixup {
+                        use_message,
+                        edit_message,
+                        commit,
                         rest,
                     });
                 }
@@ -431,21 +1616,93 @@ impl Editor for RebaseEditor {
                         code: KeyCode::Char('d'),
                         ..
                     }),
-                    Some(commit),
-                ) => {
-                    let rest = line.and_then(|l| l.get_rest()).unwrap_or_default().to_vec();
-                    self.set_current_line(RebaseTodoLine::Drop {
-                        commit: commit.to_string(),
-                        rest,
-                    });
+                    _,
+                ) => self.set_selected_lines(|commit, rest| RebaseTodoLine::Drop { commit, rest }),
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('b'),
+                        ..
+                    }),
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    self.insert_after_current_line(RebaseTodoLine::Break)
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('n'),
+                        ..
+                    }),
+                    _,
+                ) if self.mode == TodoMode::Rebase => {
+                    self.insert_after_current_line(RebaseTodoLine::Noop)
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('x'),
+                        ..
+                    }),
+                    _,
+                ) if self.mode == TodoMode::Rebase => self.open_exec_input(ExecTarget::Insert),
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('X'),
+                        ..
+                    }),
+                    _,
+                ) if self.mode == TodoMode::Rebase => self.open_exec_input(ExecTarget::EveryCommit),
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }),
+                    _,
+                ) if matches!(line, Some(RebaseTodoLine::Exec { .. })) => {
+                    self.open_exec_input(ExecTarget::Edit(self.selected()))
                 }
 
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }),
+                    _,
+                ) if matches!(line, Some(RebaseTodoLine::Reword { .. })) => {
+                    self.open_reword_input()
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('D'),
+                        ..
+                    }),
+                    _,
+                ) => self.delete_selected_lines(),
+
                 (
                     Event::Key(KeyEvent {
                         code: KeyCode::Char('q'),
                         ..
                     }),
                     _,
+                ) => {
+                    if !self.try_save()? {
+                        continue;
+                    }
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('Q'),
+                        ..
+                    }),
+                    _,
                 ) => {
                     self.save()?;
                     terminal.clear()?;
@@ -460,7 +1717,12 @@ impl Editor for RebaseEditor {
                     _,
                 ) => {
                     terminal.clear()?;
-                    self.save_empty()?;
+                    // An empty todo aborts a rebase, while an emptied
+                    // sequencer todo would silently skip the remaining
+                    // commits, so leave it untouched instead.
+                    if self.mode == TodoMode::Rebase {
+                        self.save_empty()?;
+                    }
                     return Ok(());
                 }
 
@@ -469,3 +1731,72 @@ impl Editor for RebaseEditor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editors::rebase::test_repo::TestRepo;
+
+    /// Write a todo into the repository's rebase state and open it.
+    fn open_todo(test_repo: &TestRepo, content: &str) -> RebaseEditor {
+        let state_dir = test_repo.repo.path().join("rebase-merge");
+        std::fs::create_dir_all(&state_dir).unwrap();
+        let path = state_dir.join("git-rebase-todo");
+        std::fs::write(&path, content).unwrap();
+        RebaseEditor::new(path).unwrap()
+    }
+
+    #[test]
+    fn q_refuses_to_save_lines_git_rejects() {
+        let test_repo = TestRepo::new("editor-invalid-line");
+        let first = test_repo.commit(&[("a.txt", "one\n")]);
+        let content = format!("pick {} First\nnot a command\n", first);
+
+        let mut editor = open_todo(&test_repo, &content);
+        assert!(!editor.try_save().unwrap());
+        assert!(editor.save_blocked);
+        assert_eq!(std::fs::read_to_string(&editor.path).unwrap(), content);
+    }
+
+    #[test]
+    fn edited_todo_applies_on_top_of_done_lines() {
+        let test_repo = TestRepo::new("editor-edit-todo");
+        let repo = &test_repo.repo;
+        let oid = |name: &str| repo.revparse_single(name).unwrap().id();
+
+        let base = test_repo.commit(&[("a.txt", "one\n")]);
+        let first = test_repo.commit(&[("a.txt", "two\n"), ("x.txt", "x\n")]);
+        let second = test_repo.commit(&[("a.txt", "three\n")]);
+        let other = test_repo.commit(&[("b.txt", "other\n")]);
+
+        // Stopped after picking the first commit, as after a `break`
+        let state_dir = repo.path().join("rebase-merge");
+        std::fs::create_dir_all(&state_dir).unwrap();
+        std::fs::write(state_dir.join("onto"), oid(&base).to_string()).unwrap();
+        std::fs::write(state_dir.join("orig-head"), oid(&other).to_string()).unwrap();
+        std::fs::write(
+            state_dir.join("done"),
+            format!("pick {} First\nbreak\n", first),
+        )
+        .unwrap();
+        repo.set_head_detached(oid(&first)).unwrap();
+
+        let mut editor = open_todo(
+            &test_repo,
+            &format!("pick {} Second\npick {} Other\n", second, other),
+        );
+        editor.simulate(Duration::from_secs(10));
+
+        let report = editor.report().unwrap();
+        assert!(report.simulation.conflicts.is_empty());
+        assert_eq!(report.comparison.as_ref().unwrap().files, 0);
+
+        // Dropping a remaining commit shows up against the original tip
+        editor.set_selected_lines(|commit, rest| RebaseTodoLine::Drop { commit, rest });
+        editor.simulate(Duration::from_secs(10));
+        assert_eq!(
+            editor.report().unwrap().comparison.as_ref().unwrap().files,
+            1
+        );
+    }
+}
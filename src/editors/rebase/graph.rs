@@ -0,0 +1,280 @@
+use std::ops::Range;
+
+use crate::editors::rebase::todo::{RebaseTodo, RebaseTodoLine};
+
+/// A branch of a `--rebase-merges` todo: the lines from a `reset` up to the
+/// next one. Lines before the first `reset` form the first lane.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lane {
+    pub lines: Range<usize>,
+    /// The label naming the branch tip, the last label in the lane
+    pub label: Option<String>,
+    /// The line the lane ends on: the merge that merges its label, or its
+    /// own last line
+    pub end: usize,
+    pub column: usize,
+}
+
+impl Lane {
+    fn is_active(&self, idx: usize) -> bool {
+        self.lines.start <= idx && idx <= self.end
+    }
+}
+
+/// Branch lanes of a todo, drawn like `git log --graph` but oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct TodoGraph {
+    pub lanes: Vec<Lane>,
+    width: usize,
+}
+
+impl TodoGraph {
+    pub fn new(todo: &RebaseTodo) -> Self {
+        let mut starts = todo
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| matches!(line, RebaseTodoLine::Reset { .. }))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        if starts.first() != Some(&0) {
+            starts.insert(0, 0);
+        }
+
+        let mut lanes: Vec<Lane> = vec![];
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(todo.len());
+            let label = (start..end).rev().find_map(|idx| match todo.get(idx) {
+                Some(RebaseTodoLine::Label { label, .. }) => Some(label.clone()),
+                _ => None,
+            });
+
+            // A merged lane stays drawn until the merge that joins it
+            let merged_at = label.as_ref().and_then(|label| {
+                (end..todo.len()).find(|&idx| {
+                    todo.get(idx)
+                        .is_some_and(|line| line.get_merge_labels().contains(&label.as_str()))
+                })
+            });
+
+            let column = (0..)
+                .find(|&column| {
+                    !lanes
+                        .iter()
+                        .any(|lane| lane.column == column && lane.is_active(start))
+                })
+                .unwrap_or(0);
+
+            lanes.push(Lane {
+                lines: start..end,
+                label,
+                end: merged_at.unwrap_or(end.saturating_sub(1).max(start)),
+                column,
+            });
+        }
+
+        let width = lanes.iter().map(|lane| lane.column + 1).max().unwrap_or(0);
+        TodoGraph { lanes, width }
+    }
+
+    /// The number of columns the graph takes up.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Whether there is more than one lane to draw.
+    pub fn is_branched(&self) -> bool {
+        self.lanes.len() > 1
+    }
+
+    /// The lane a line belongs to.
+    pub fn lane_of(&self, idx: usize) -> Option<usize> {
+        self.lanes.iter().position(|lane| lane.lines.contains(&idx))
+    }
+
+    fn node(line: &RebaseTodoLine) -> char {
+        match line {
+            RebaseTodoLine::Merge { .. } => '◆',
+            RebaseTodoLine::Drop { .. } => '○',
+            RebaseTodoLine::Reset { .. } => '┬',
+            RebaseTodoLine::Label { .. } => '◇',
+            RebaseTodoLine::Comment { .. } => '│',
+            line if line.creates_commit() => '●',
+            _ => '·',
+        }
+    }
+
+    /// The graph drawn before a line, two characters per column.
+    pub fn prefix(&self, idx: usize, line: &RebaseTodoLine) -> String {
+        let Some(current) = self.lane_of(idx).map(|lane| self.lanes[lane].column) else {
+            return String::new();
+        };
+
+        // Columns of lanes merged on this line are joined to the current one
+        let merged = self
+            .lanes
+            .iter()
+            .filter(|lane| lane.end == idx && !lane.lines.contains(&idx))
+            .map(|lane| lane.column)
+            .collect::<Vec<_>>();
+        let span_start = merged
+            .iter()
+            .copied()
+            .chain([current])
+            .min()
+            .unwrap_or(current);
+        let span_end = merged
+            .iter()
+            .copied()
+            .chain([current])
+            .max()
+            .unwrap_or(current);
+
+        let mut prefix = String::new();
+        for column in 0..self.width {
+            let passing = self
+                .lanes
+                .iter()
+                .any(|lane| lane.column == column && lane.is_active(idx));
+            let joined = column > span_start && column < span_end;
+
+            prefix.push(if column == current {
+                Self::node(line)
+            } else if merged.contains(&column) {
+                if column < current { '└' } else { '┘' }
+            } else if joined && passing {
+                '┼'
+            } else if joined {
+                '─'
+            } else if passing {
+                '│'
+            } else {
+                ' '
+            });
+            prefix.push(if column >= span_start && column < span_end {
+                '─'
+            } else {
+                ' '
+            });
+        }
+
+        prefix
+    }
+
+    /// Where a commit moved into a lane goes: before the label naming the
+    /// lane's tip, so the branch and whatever merges it include the commit.
+    pub fn insertion_point(&self, todo: &RebaseTodo, lane: usize) -> usize {
+        let lane = &self.lanes[lane];
+        let tip = lane.lines.clone().rev().find(|&idx| match todo.get(idx) {
+            Some(RebaseTodoLine::Label { label, .. }) => Some(label) == lane.label.as_ref(),
+            _ => false,
+        });
+
+        tip.unwrap_or_else(|| {
+            // Without a label, after the last line that is not a comment
+            lane.lines
+                .clone()
+                .rev()
+                .find(|&idx| !matches!(todo.get(idx), Some(RebaseTodoLine::Comment { .. })))
+                .map_or(lane.lines.start, |idx| idx + 1)
+        })
+    }
+
+    /// The lanes a commit can be moved into: every branch started by a
+    /// `reset`, and the first lane if it already has commits.
+    pub fn accepts_commits(&self, todo: &RebaseTodo, lane: usize) -> bool {
+        let lines = self.lanes[lane].lines.clone();
+        matches!(todo.get(lines.start), Some(RebaseTodoLine::Reset { .. }))
+            || lines
+                .filter_map(|idx| todo.get(idx))
+                .any(|line| line.creates_commit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editors::rebase::todo::DEFAULT_COMMENT;
+
+    const TODO: &str = "label onto
+
+reset [new root]
+pick 560c8b9 base
+label branch-point
+pick dceeac0 feat one
+pick d4a2990 feat two
+label feat
+
+reset branch-point # base
+pick 5f62650 main one
+label branch-point-2
+pick 4ec553c fix x
+label fix
+
+reset branch-point-2 # main one
+merge -C 674bbf5 feat # Merge branch 'feat'
+pick 5e9512b main two
+merge -C 24c6aaf fix # Merge branch 'fix'
+";
+
+    #[test]
+    fn lanes_follow_resets_and_merges() {
+        let todo = RebaseTodo::parse(TODO, DEFAULT_COMMENT);
+        let graph = TodoGraph::new(&todo);
+
+        let lanes = graph
+            .lanes
+            .iter()
+            .map(|lane| {
+                (
+                    lane.lines.clone(),
+                    lane.label.as_deref(),
+                    lane.end,
+                    lane.column,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lanes,
+            vec![
+                (0..2, Some("onto"), 1, 0),
+                (2..9, Some("feat"), 16, 0),
+                (9..15, Some("fix"), 18, 1),
+                (15..19, None, 18, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefix_draws_lanes_and_merges() {
+        let todo = RebaseTodo::parse(TODO, DEFAULT_COMMENT);
+        let graph = TodoGraph::new(&todo);
+        let prefix = |idx| graph.prefix(idx, todo.get(idx).unwrap());
+
+        assert_eq!(prefix(3), "●     ");
+        assert_eq!(prefix(10), "│ ●   ");
+        assert_eq!(prefix(15), "│ │ ┬ ");
+        assert_eq!(prefix(16), "└─┼─◆ ");
+        assert_eq!(prefix(17), "  │ ● ");
+        assert_eq!(prefix(18), "  └─◆ ");
+    }
+
+    #[test]
+    fn commits_move_before_the_tip_label() {
+        let todo = RebaseTodo::parse(TODO, DEFAULT_COMMENT);
+        let graph = TodoGraph::new(&todo);
+
+        assert_eq!(graph.insertion_point(&todo, 1), 7);
+        assert_eq!(graph.insertion_point(&todo, 2), 13);
+        assert_eq!(graph.insertion_point(&todo, 3), 19);
+        assert!(!graph.accepts_commits(&todo, 0));
+        assert!(graph.accepts_commits(&todo, 3));
+    }
+
+    #[test]
+    fn linear_todo_is_a_single_lane() {
+        let todo = RebaseTodo::parse("pick a One\npick b Two\n", DEFAULT_COMMENT);
+        let graph = TodoGraph::new(&todo);
+        assert!(!graph.is_branched());
+        assert_eq!(graph.prefix(1, todo.get(1).unwrap()), "● ");
+    }
+}
@@ -0,0 +1,91 @@
+/// Undo and redo stacks of labelled snapshots.
+///
+/// Snapshots are taken before each change, so undoing swaps the current
+/// state for the one before the most recent change, and redoing swaps it
+/// back.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    undo: Vec<(String, T)>,
+    redo: Vec<(String, T)>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History {
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Record the state from before a change. Any undone changes can no
+    /// longer be redone.
+    pub fn record(&mut self, label: impl Into<String>, before: T) {
+        self.undo.push((label.into(), before));
+        self.redo.clear();
+    }
+
+    /// The state to restore to undo the most recent change.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let (label, previous) = self.undo.pop()?;
+        self.redo.push((label, current));
+        Some(previous)
+    }
+
+    /// The state to restore to redo the most recently undone change.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let (label, next) = self.redo.pop()?;
+        self.undo.push((label, current));
+        Some(next)
+    }
+
+    /// Applied changes, oldest first.
+    pub fn applied(&self) -> impl Iterator<Item = &str> {
+        self.undo.iter().map(|(label, _)| label.as_str())
+    }
+
+    /// Undone changes, in the order they would be redone.
+    pub fn undone(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|(label, _)| label.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_restore_states() {
+        let mut history = History::default();
+        history.record("first", 0);
+        history.record("second", 1);
+        let current = 2;
+
+        let current = history.undo(current).unwrap();
+        assert_eq!(current, 1);
+        let current = history.undo(current).unwrap();
+        assert_eq!(current, 0);
+        assert_eq!(history.undo(current), None);
+
+        let current = history.redo(current).unwrap();
+        assert_eq!(current, 1);
+        assert_eq!(history.applied().collect::<Vec<_>>(), vec!["first"]);
+        assert_eq!(history.undone().collect::<Vec<_>>(), vec!["second"]);
+
+        let current = history.redo(current).unwrap();
+        assert_eq!(current, 2);
+        assert_eq!(history.redo(current), None);
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = History::default();
+        history.record("first", 0);
+        history.undo(1);
+        history.record("other", 0);
+
+        assert_eq!(history.redo(1), None);
+        assert_eq!(history.applied().collect::<Vec<_>>(), vec!["other"]);
+    }
+}
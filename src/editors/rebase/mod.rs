@@ -1,4 +1,12 @@
 pub mod editor;
+pub mod graph;
+pub mod history;
+pub mod sequencer;
+pub mod simulate;
+#[cfg(test)]
+pub mod test_repo;
 pub mod todo;
+pub mod validate;
 
 pub use editor::RebaseEditor;
+pub use sequencer::SequencerEditor;
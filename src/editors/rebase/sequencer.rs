@@ -0,0 +1,50 @@
+use crate::editors::{
+    Editor,
+    rebase::{
+        RebaseEditor,
+        editor::TodoMode,
+        todo::{RebaseTodo, RebaseTodoLine, comment_string},
+    },
+};
+use git2::Repository;
+use std::path::{Path, PathBuf};
+
+/// The todo of a multi-commit `git cherry-pick` or `git revert`, edited with
+/// the rebase editor restricted to reordering and dropping commits.
+pub struct SequencerEditor {
+    editor: RebaseEditor,
+}
+
+impl SequencerEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let content = std::fs::read_to_string(&path)?;
+        let revert = RebaseTodo::parse(&content, &comment_string(&repo))
+            .lines()
+            .any(|line| matches!(line, RebaseTodoLine::Revert { .. }));
+
+        let editor = RebaseEditor::with_mode(path, TodoMode::Sequencer { revert })?;
+        Ok(Self { editor })
+    }
+}
+
+impl Editor for SequencerEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("todo"))
+            && path
+                .parent()
+                .and_then(|p| p.file_name())
+                .is_some_and(|p| p.eq("sequencer"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        self.editor.render(frame);
+    }
+
+    fn run(&mut self, terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        self.editor.run(terminal)
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use git2::{Diff, Index, Oid, Repository};
+use ratatui::text::Line;
+
+use crate::editors::{
+    diff::diff_lines,
+    rebase::todo::{RebaseTodo, RebaseTodoLine},
+};
+
+/// The outcome of running a todo in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    /// Paths that would conflict, by todo line
+    pub conflicts: HashMap<usize, Vec<String>>,
+    /// The tree the rebase ends with, resolving each conflict in favour of
+    /// the commit being applied
+    pub result: Oid,
+}
+
+/// How the simulated result differs from the original branch tip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    pub files: usize,
+    pub lines: Vec<Line<'static>>,
+}
+
+/// A simulation along with its comparison, which has to be made while the
+/// trees the simulation wrote are still in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub simulation: Simulation,
+    pub comparison: Option<Comparison>,
+}
+
+/// Runs todos against a separate handle on the repository whose new objects
+/// are kept in memory, so simulating never writes to the object database.
+pub struct Simulator {
+    repo: Repository,
+}
+
+enum Outcome {
+    Clean(Oid),
+    Conflict { paths: Vec<String>, resolved: Oid },
+}
+
+/// The bits of an index entry's flags holding its conflict stage.
+const STAGE_MASK: u16 = 0x3000;
+
+impl Simulator {
+    pub fn new(git_dir: &Path) -> Result<Self, git2::Error> {
+        let repo = Repository::open(git_dir)?;
+        repo.odb()?.add_new_mempack_backend(1000)?;
+        Ok(Simulator { repo })
+    }
+
+    /// Resolve every conflict in favour of the commit being applied,
+    /// returning the conflicted paths.
+    fn take_theirs(index: &mut Index) -> Result<Vec<String>, git2::Error> {
+        let conflicts = index.conflicts()?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut paths = vec![];
+        for conflict in conflicts {
+            let Some(entry) = conflict
+                .our
+                .as_ref()
+                .or(conflict.their.as_ref())
+                .or(conflict.ancestor.as_ref())
+            else {
+                continue;
+            };
+            let path = String::from_utf8_lossy(&entry.path).into_owned();
+            index.conflict_remove(Path::new(&path))?;
+            if let Some(mut their) = conflict.their {
+                their.flags &= !STAGE_MASK;
+                index.add(&their)?;
+            }
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Three-way merge of trees, as a cherry-pick, revert or merge does.
+    fn merge(&self, ancestor: Oid, ours: Oid, theirs: Oid) -> Result<Outcome, git2::Error> {
+        let repo = &self.repo;
+        let mut index = repo.merge_trees(
+            &repo.find_tree(ancestor)?,
+            &repo.find_tree(ours)?,
+            &repo.find_tree(theirs)?,
+            None,
+        )?;
+
+        if index.has_conflicts() {
+            let paths = Self::take_theirs(&mut index)?;
+            let resolved = index.write_tree_to(repo)?;
+            Ok(Outcome::Conflict { paths, resolved })
+        } else {
+            Ok(Outcome::Clean(index.write_tree_to(repo)?))
+        }
+    }
+
+    fn empty_tree(&self) -> Result<Oid, git2::Error> {
+        self.repo.treebuilder(None)?.write()
+    }
+
+    fn tree_of(&self, name: &str) -> Option<Oid> {
+        let object = self.repo.revparse_single(name).ok()?;
+        object.peel_to_tree().ok().map(|tree| tree.id())
+    }
+
+    /// A label from lines that already ran is kept as a ref, otherwise the
+    /// name can be any revision.
+    fn label_tree(&self, label: &str) -> Option<Oid> {
+        self.tree_of(&format!("refs/rewritten/{}", label))
+            .or_else(|| self.tree_of(label))
+    }
+
+    /// A commit's tree and its first parent's tree.
+    fn commit_trees(&self, commit: &str) -> Result<(Oid, Oid), git2::Error> {
+        let commit = self.repo.revparse_single(commit)?.peel_to_commit()?;
+        let parent = match commit.parent(0) {
+            Ok(parent) => parent.tree_id(),
+            Err(_) => self.empty_tree()?,
+        };
+        Ok((commit.tree_id(), parent))
+    }
+
+    /// Apply the todo on top of `onto`. Lines whose commit cannot be found
+    /// are skipped, and conflicts are resolved in favour of the commit so
+    /// later lines are still checked.
+    pub fn run(&self, todo: &RebaseTodo, onto: Oid) -> Result<Simulation, git2::Error> {
+        let mut current = self.repo.find_commit(onto)?.tree_id();
+        // The tree the current branch was reset to, the closest thing to a
+        // merge base for the branches of a `--rebase-merges` todo
+        let mut base = current;
+        let mut labels: HashMap<&str, (Oid, Oid)> = HashMap::new();
+        let mut conflicts = HashMap::new();
+
+        for (idx, line) in todo.lines().enumerate() {
+            let outcome = match line {
+                RebaseTodoLine::Pick { commit, .. }
+                | RebaseTodoLine::Edit { commit, .. }
+                | RebaseTodoLine::Reword { commit, .. }
+                | RebaseTodoLine::Squash { commit, .. }
+                | RebaseTodoLine::Fixup { commit, .. } => {
+                    let Ok((tree, parent)) = self.commit_trees(commit) else {
+                        continue;
+                    };
+                    Some(self.merge(parent, current, tree)?)
+                }
+                RebaseTodoLine::Revert { commit, .. } => {
+                    let Ok((tree, parent)) = self.commit_trees(commit) else {
+                        continue;
+                    };
+                    Some(self.merge(tree, current, parent)?)
+                }
+                RebaseTodoLine::Label { label, .. } => {
+                    labels.insert(label, (current, base));
+                    None
+                }
+                RebaseTodoLine::Reset { label, .. } => {
+                    let tree = match labels.get(label.as_str()) {
+                        Some(&(tree, _)) => Some(tree),
+                        None if label == "[new" => Some(self.empty_tree()?),
+                        None => self.label_tree(label),
+                    };
+                    if let Some(tree) = tree {
+                        current = tree;
+                        base = tree;
+                    }
+                    None
+                }
+                RebaseTodoLine::Merge { .. } => {
+                    let mut paths = vec![];
+                    for label in line.get_merge_labels() {
+                        let Some((theirs, their_base)) = labels
+                            .get(label)
+                            .copied()
+                            .or_else(|| self.label_tree(label).map(|tree| (tree, base)))
+                        else {
+                            continue;
+                        };
+                        match self.merge(their_base, current, theirs)? {
+                            Outcome::Clean(tree) => current = tree,
+                            Outcome::Conflict {
+                                paths: conflicted,
+                                resolved,
+                            } => {
+                                paths.extend(conflicted);
+                                current = resolved;
+                            }
+                        }
+                    }
+                    (!paths.is_empty()).then_some(Outcome::Conflict {
+                        paths,
+                        resolved: current,
+                    })
+                }
+                _ => None,
+            };
+
+            match outcome {
+                Some(Outcome::Clean(tree)) => current = tree,
+                Some(Outcome::Conflict { paths, resolved }) => {
+                    conflicts.insert(idx, paths);
+                    current = resolved;
+                }
+                None => {}
+            }
+        }
+
+        Ok(Simulation {
+            conflicts,
+            result: current,
+        })
+    }
+
+    /// The changes from a commit's tree to the simulated result.
+    pub fn diff_result(
+        &self,
+        original: Oid,
+        simulation: &Simulation,
+    ) -> Result<Diff<'_>, git2::Error> {
+        let original = self.repo.find_commit(original)?.tree()?;
+        let result = self.repo.find_tree(simulation.result)?;
+        self.repo
+            .diff_tree_to_tree(Some(&original), Some(&result), None)
+    }
+
+    pub fn compare(
+        &self,
+        original: Oid,
+        simulation: &Simulation,
+    ) -> Result<Comparison, git2::Error> {
+        let diff = self.diff_result(original, simulation)?;
+        Ok(Comparison {
+            files: diff.deltas().len(),
+            lines: diff_lines(&diff).unwrap_or_default(),
+        })
+    }
+}
+
+/// Simulates todos on a background thread, so long todos do not hold up
+/// drawing. Every run starts with an empty in-memory object store, and when
+/// several todos are waiting only the latest is run.
+pub struct BackgroundSimulator {
+    requests: Sender<String>,
+    reports: Receiver<(String, Option<Report>)>,
+}
+
+impl BackgroundSimulator {
+    /// Start the thread. Todos are applied on top of `onto` and the result
+    /// compared with `original`.
+    pub fn spawn(git_dir: PathBuf, comment: String, onto: Oid, original: Option<Oid>) -> Self {
+        let (requests, pending) = mpsc::channel::<String>();
+        let (finished, reports) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            while let Ok(content) = pending.recv() {
+                let content = pending.try_iter().last().unwrap_or(content);
+                let report = Self::report(&git_dir, &content, &comment, onto, original).ok();
+                if finished.send((content, report)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        BackgroundSimulator { requests, reports }
+    }
+
+    fn report(
+        git_dir: &Path,
+        content: &str,
+        comment: &str,
+        onto: Oid,
+        original: Option<Oid>,
+    ) -> Result<Report, git2::Error> {
+        let simulator = Simulator::new(git_dir)?;
+        let todo = RebaseTodo::parse(content, comment);
+        let simulation = simulator.run(&todo, onto)?;
+        let comparison =
+            original.and_then(|original| simulator.compare(original, &simulation).ok());
+
+        Ok(Report {
+            simulation,
+            comparison,
+        })
+    }
+
+    pub fn request(&self, content: String) {
+        let _ = self.requests.send(content);
+    }
+
+    /// A finished run and the todo it was for, waiting up to `timeout`.
+    pub fn next_report(&self, timeout: Duration) -> Option<(String, Option<Report>)> {
+        self.reports.recv_timeout(timeout).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editors::rebase::{test_repo::TestRepo, todo::DEFAULT_COMMENT};
+
+    #[test]
+    fn reordering_dependent_commits_conflicts() {
+        let test_repo = TestRepo::new("simulate");
+        let repo = &test_repo.repo;
+
+        test_repo.commit(&[("a.txt", "one\n")]);
+        let onto = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let first = test_repo.commit(&[("a.txt", "two\n")]);
+        let second = test_repo.commit(&[("a.txt", "three\n")]);
+        let other = test_repo.commit(&[("b.txt", "other\n")]);
+
+        let simulator = Simulator::new(repo.path()).unwrap();
+        let run = |content: String| {
+            let todo = RebaseTodo::parse(&content, DEFAULT_COMMENT);
+            simulator.run(&todo, onto).unwrap()
+        };
+
+        let in_order = run(format!(
+            "pick {} First\npick {} Second\npick {} Other\n",
+            first, second, other
+        ));
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        assert!(in_order.conflicts.is_empty());
+        let diff = simulator.diff_result(head, &in_order).unwrap();
+        assert_eq!(diff.deltas().len(), 0);
+
+        // Dropping a commit shows what disappears
+        let dropped = run(format!(
+            "pick {} First\npick {} Second\ndrop {} Other\n",
+            first, second, other
+        ));
+        let diff = simulator.diff_result(head, &dropped).unwrap();
+        let deltas = diff
+            .deltas()
+            .map(|delta| (delta.status(), delta.old_file().path().unwrap().to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deltas,
+            vec![(git2::Delta::Deleted, Path::new("b.txt").to_owned())]
+        );
+
+        let swapped = run(format!(
+            "pick {} Other\npick {} Second\npick {} First\n",
+            other, second, first
+        ));
+        assert_eq!(
+            swapped.conflicts,
+            HashMap::from([
+                (1, vec!["a.txt".to_string()]),
+                (2, vec!["a.txt".to_string()])
+            ])
+        );
+
+        // Resolving in favour of each commit keeps the earlier changes
+        let tree = simulator.repo.find_tree(swapped.result).unwrap();
+        assert!(tree.get_name("b.txt").is_some());
+
+        // The new tree only exists in memory
+        assert!(repo.find_tree(swapped.result).is_err());
+    }
+}
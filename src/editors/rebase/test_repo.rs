@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+/// A repository in a temporary directory, removed when dropped.
+pub struct TestRepo {
+    pub repo: Repository,
+    dir: PathBuf,
+}
+
+impl TestRepo {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("glitt-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let repo = Repository::init(&dir).unwrap();
+        TestRepo { repo, dir }
+    }
+
+    /// Commit files on top of HEAD, returning the short id.
+    pub fn commit(&self, files: &[(&str, &str)]) -> String {
+        let repo = &self.repo;
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            std::fs::write(self.dir.join(path), content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parents = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let oid = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "commit",
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        oid.to_string()[..7].to_string()
+    }
+}
+
+impl Drop for TestRepo {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -1,9 +1,33 @@
 use std::fmt::Display;
 
 use clap::Parser;
+use git2::Repository;
 use ratatui::style::{Color, Modifier, Style};
 
-#[derive(clap::Subcommand, Debug)]
+/// The comment marker git uses unless configured otherwise.
+pub const DEFAULT_COMMENT: &str = "#";
+
+/// The marker git starts comment lines with, from `core.commentString` or
+/// its older spelling `core.commentChar`. `auto` makes git pick a character
+/// that does not clash with the message, which for a todo list is `#`.
+pub fn comment_string(repo: &Repository) -> String {
+    let config = repo.config().ok();
+    let get = |key| {
+        config
+            .as_ref()
+            .and_then(|config| config.get_string(key).ok())
+    };
+    resolve_comment_string(get("core.commentString"), get("core.commentChar"))
+}
+
+fn resolve_comment_string(comment_string: Option<String>, comment_char: Option<String>) -> String {
+    match comment_string.or(comment_char) {
+        Some(marker) if !marker.is_empty() && marker != "auto" => marker,
+        _ => DEFAULT_COMMENT.to_string(),
+    }
+}
+
+#[derive(clap::Subcommand, Clone, Debug, PartialEq)]
 pub enum RebaseTodoLine {
     #[command(skip)]
     Comment { message: String },
@@ -15,6 +39,13 @@ pub enum RebaseTodoLine {
         rest: Vec<String>,
     },
 
+    /// Only valid in the sequencer todo of `git revert`
+    Revert {
+        commit: String,
+        #[arg(num_args = 1.., trailing_var_arg = true)]
+        rest: Vec<String>,
+    },
+
     #[command(alias = "e")]
     Edit {
         commit: String,
@@ -38,16 +69,27 @@ pub enum RebaseTodoLine {
 
     #[command(alias = "f")]
     Fixup {
+        /// `-C`, use this commit's message instead of the combined one
+        #[arg(short = 'C')]
+        use_message: bool,
+        /// `-c`, like `-C` but open the editor to change the message
+        #[arg(short = 'c', conflicts_with = "use_message")]
+        edit_message: bool,
         commit: String,
         #[arg(num_args = 1.., trailing_var_arg = true)]
         rest: Vec<String>,
     },
 
-    #[command(alias = "x")]
-    Exec {
-        #[arg(num_args = 1.., trailing_var_arg = true)]
-        command: Vec<String>,
-    },
+    /// The command is kept verbatim since the shell, not git, splits it
+    #[command(skip)]
+    Exec { command: String },
+
+    /// Stop the rebase here, resume with `git rebase --continue`
+    #[command(alias = "b")]
+    Break,
+
+    /// Does nothing, git writes it when there is nothing to rebase
+    Noop,
 
     #[command(alias = "d")]
     Drop {
@@ -72,9 +114,17 @@ pub enum RebaseTodoLine {
 
     #[command(alias = "m")]
     Merge {
-        #[arg(short = 'c', alias = "C")]
+        /// `-C`, reuse the message of the original merge commit
+        #[arg(short = 'C')]
         commit: Option<String>,
+        /// `-c`, like `-C` but open the editor to change the message
+        #[arg(short = 'c', conflicts_with = "commit")]
+        edit_commit: Option<String>,
         label: String,
+        /// The other labels of an octopus merge, then the `# subject` git
+        /// adds
+        #[arg(num_args = 1.., trailing_var_arg = true)]
+        rest: Vec<String>,
     },
 
     #[command(alias = "u")]
@@ -93,11 +143,14 @@ impl RebaseTodoLine {
         match self {
             RebaseTodoLine::Comment { .. } => Color::White,
             RebaseTodoLine::Pick { .. } => Color::White,
+            RebaseTodoLine::Revert { .. } => Color::Cyan,
             RebaseTodoLine::Edit { .. } => Color::Blue,
             RebaseTodoLine::Reword { .. } => Color::Magenta,
             RebaseTodoLine::Squash { .. } => Color::Yellow,
             RebaseTodoLine::Fixup { .. } => Color::LightYellow,
             RebaseTodoLine::Exec { .. } => Color::Red,
+            RebaseTodoLine::Break => Color::LightRed,
+            RebaseTodoLine::Noop => Color::DarkGray,
             RebaseTodoLine::Drop { .. } => Color::White,
             RebaseTodoLine::Label { .. } => Color::White,
             RebaseTodoLine::Reset { .. } => Color::White,
@@ -106,6 +159,19 @@ impl RebaseTodoLine {
         }
     }
 
+    /// The modifier marking a fixup that takes the commit's message.
+    fn get_fixup_modifier(&self) -> Modifier {
+        match self {
+            RebaseTodoLine::Fixup {
+                use_message: true, ..
+            } => Modifier::ITALIC,
+            RebaseTodoLine::Fixup {
+                edit_message: true, ..
+            } => Modifier::ITALIC | Modifier::UNDERLINED,
+            _ => Modifier::empty(),
+        }
+    }
+
     pub fn get_style(&self) -> Style {
         let color = self.get_color();
         match self {
@@ -116,7 +182,9 @@ impl RebaseTodoLine {
                 .fg(color)
                 .add_modifier(Modifier::CROSSED_OUT)
                 .add_modifier(Modifier::DIM),
-            _ => Style::default().fg(color),
+            _ => Style::default()
+                .fg(color)
+                .add_modifier(self.get_fixup_modifier()),
         }
     }
 
@@ -137,13 +205,29 @@ impl RebaseTodoLine {
             _ => Style::default()
                 .bg(color)
                 .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
+                .add_modifier(Modifier::BOLD)
+                .add_modifier(self.get_fixup_modifier()),
         }
     }
 
+    /// Whether the line makes a commit that later lines build on.
+    pub fn creates_commit(&self) -> bool {
+        matches!(
+            self,
+            RebaseTodoLine::Pick { .. }
+                | RebaseTodoLine::Revert { .. }
+                | RebaseTodoLine::Edit { .. }
+                | RebaseTodoLine::Reword { .. }
+                | RebaseTodoLine::Squash { .. }
+                | RebaseTodoLine::Fixup { .. }
+                | RebaseTodoLine::Merge { .. }
+        )
+    }
+
     pub fn get_commit(&self) -> Option<&str> {
         match &self {
             RebaseTodoLine::Pick { commit, .. } => Some(commit),
+            RebaseTodoLine::Revert { commit, .. } => Some(commit),
             RebaseTodoLine::Edit { commit, .. } => Some(commit),
             RebaseTodoLine::Squash { commit, .. } => Some(commit),
             RebaseTodoLine::Fixup { commit, .. } => Some(commit),
@@ -156,7 +240,9 @@ impl RebaseTodoLine {
     pub fn get_rest(&self) -> Option<&[String]> {
         match &self {
             RebaseTodoLine::Pick { rest, .. } => Some(rest),
+            RebaseTodoLine::Revert { rest, .. } => Some(rest),
             RebaseTodoLine::Edit { rest, .. } => Some(rest),
+            RebaseTodoLine::Reword { rest, .. } => Some(rest),
             RebaseTodoLine::Squash { rest, .. } => Some(rest),
             RebaseTodoLine::Fixup { rest, .. } => Some(rest),
             RebaseTodoLine::Drop { rest, .. } => Some(rest),
@@ -164,14 +250,32 @@ impl RebaseTodoLine {
         }
     }
 
+    /// The labels a merge line merges, in order. git always starts the
+    /// subject after them with `#`, whatever the comment string.
+    pub fn get_merge_labels(&self) -> Vec<&str> {
+        match self {
+            RebaseTodoLine::Merge { label, rest, .. } => std::iter::once(label.as_str())
+                .chain(
+                    rest.iter()
+                        .map(String::as_str)
+                        .take_while(|word| !word.starts_with('#')),
+                )
+                .collect(),
+            _ => vec![],
+        }
+    }
+
     pub fn get_action(&self) -> &str {
         match self {
             RebaseTodoLine::Comment { .. } => "comment",
             RebaseTodoLine::Pick { .. } => "pick",
+            RebaseTodoLine::Revert { .. } => "revert",
             RebaseTodoLine::Edit { .. } => "edit",
             RebaseTodoLine::Squash { .. } => "squash",
             RebaseTodoLine::Fixup { .. } => "fixup",
             RebaseTodoLine::Exec { .. } => "exec",
+            RebaseTodoLine::Break => "break",
+            RebaseTodoLine::Noop => "noop",
             RebaseTodoLine::Drop { .. } => "drop",
             RebaseTodoLine::Label { .. } => "label",
             RebaseTodoLine::Reset { .. } => "reset",
@@ -182,59 +286,105 @@ impl RebaseTodoLine {
     }
 }
 
+/// Write `action arg rest...`, omitting the separator when `rest` is empty.
+fn write_line(
+    f: &mut std::fmt::Formatter<'_>,
+    action: &str,
+    arg: &str,
+    rest: &[String],
+) -> std::fmt::Result {
+    if rest.is_empty() {
+        write!(f, "{} {}", action, arg)
+    } else {
+        write!(f, "{} {} {}", action, arg, rest.join(" "))
+    }
+}
+
 impl Display for RebaseTodoLine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let action = self.get_action();
         match self {
             RebaseTodoLine::Comment { message } => write!(f, "{}", message),
-            RebaseTodoLine::Pick { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
-            RebaseTodoLine::Edit { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
-            RebaseTodoLine::Squash { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
-            RebaseTodoLine::Fixup { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
-            RebaseTodoLine::Exec { command } => {
-                write!(f, "{} {}", action, command.join(" "))
-            }
-            RebaseTodoLine::Drop { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
-            RebaseTodoLine::Label { label, rest } => {
-                write!(f, "{} {} {}", action, label, rest.join(" "))
-            }
-            RebaseTodoLine::Reset { label, rest } => {
-                write!(f, "{} {} {}", action, label, rest.join(" "))
-            }
-            RebaseTodoLine::Merge { commit, label } => {
-                if let Some(c) = commit {
-                    write!(f, "{} -c {} {}", action, c, label)
-                } else {
-                    write!(f, "{} {}", action, label)
-                }
+            RebaseTodoLine::Pick { commit, rest } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Revert { commit, rest } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Edit { commit, rest } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Squash { commit, rest } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Fixup {
+                use_message: true,
+                commit,
+                rest,
+                ..
+            } => write_line(f, &format!("{} -C", action), commit, rest),
+            RebaseTodoLine::Fixup {
+                edit_message: true,
+                commit,
+                rest,
+                ..
+            } => write_line(f, &format!("{} -c", action), commit, rest),
+            RebaseTodoLine::Fixup { commit, rest, .. } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Exec { command } => write!(f, "{} {}", action, command),
+            RebaseTodoLine::Break | RebaseTodoLine::Noop => write!(f, "{}", action),
+            RebaseTodoLine::Drop { commit, rest } => write_line(f, action, commit, rest),
+            RebaseTodoLine::Label { label, rest } => write_line(f, action, label, rest),
+            RebaseTodoLine::Reset { label, rest } => write_line(f, action, label, rest),
+            RebaseTodoLine::Merge {
+                commit,
+                edit_commit,
+                label,
+                rest,
+            } => {
+                let action = match (commit, edit_commit) {
+                    (Some(c), _) => format!("{} -C {}", action, c),
+                    (None, Some(c)) => format!("{} -c {}", action, c),
+                    (None, None) => action.to_string(),
+                };
+                write_line(f, &action, label, rest)
             }
             RebaseTodoLine::UpdateRef { refname } => write!(f, "{} {}", action, refname),
-            RebaseTodoLine::Reword { commit, rest } => {
-                write!(f, "{} {} {}", action, commit, rest.join(" "))
-            }
+            RebaseTodoLine::Reword { commit, rest } => write_line(f, action, commit, rest),
         }
     }
 }
 
 impl RebaseTodoLine {
-    pub fn parse(line: &str) -> Self {
+    /// The command of an `exec` line, everything after the action.
+    fn parse_exec(line: &str) -> Option<&str> {
+        let (action, command) = line.split_once(char::is_whitespace)?;
+        matches!(action, "exec" | "x").then(|| command.trim_start())
+    }
+
+    pub fn parse(line: &str, comment: &str) -> Self {
         let line = line.trim();
-        if line.starts_with('#') || line.is_empty() {
+        if line.starts_with(comment) || line.is_empty() {
             RebaseTodoLine::Comment {
                 message: line.to_string(),
             }
+        } else if let Some(command) = Self::parse_exec(line) {
+            RebaseTodoLine::Exec {
+                command: command.to_string(),
+            }
         } else {
-            RebaseTodoLineParser::try_parse_from(line.split_whitespace())
+            let mut words = line.split_whitespace().collect::<Vec<_>>();
+
+            // Options only come before the commit or label, so everything
+            // after it is message text even if it looks like `-c` or `--help`
+            let value_options: &[&str] = match words.first() {
+                Some(&"merge" | &"m") => &["-C", "-c"],
+                _ => &[],
+            };
+            let mut idx = 1;
+            while idx < words.len() && words[idx].starts_with('-') {
+                idx += if value_options.contains(&words[idx]) {
+                    2
+                } else {
+                    1
+                };
+            }
+            if idx < words.len() {
+                words.insert(idx + 1, "--");
+            }
+
+            RebaseTodoLineParser::try_parse_from(words)
                 .map(|parser| parser.line)
                 .unwrap_or(RebaseTodoLine::Comment {
                     message: line.to_string(),
@@ -243,49 +393,222 @@ impl RebaseTodoLine {
     }
 }
 
+/// A todo line along with the text it was parsed from. The text is kept
+/// until the line is changed, so untouched lines are written back verbatim.
+#[derive(Clone, Debug)]
+struct TodoEntry {
+    line: RebaseTodoLine,
+    raw: Option<String>,
+}
+
+#[derive(Clone, Debug)]
 pub struct RebaseTodo {
-    lines: Vec<RebaseTodoLine>,
+    entries: Vec<TodoEntry>,
+    /// Whether lines end in `\r\n`, so changed lines match the rest
+    crlf: bool,
+    trailing_newline: bool,
 }
 
 impl RebaseTodo {
-    pub fn parse(content: &str) -> Self {
-        let lines = content
-            .lines()
-            .map(RebaseTodoLine::parse)
-            .collect::<Vec<_>>();
-        RebaseTodo { lines }
+    pub fn parse(content: &str, comment: &str) -> Self {
+        let trailing_newline = content.ends_with('\n');
+        let body = content.strip_suffix('\n').unwrap_or(content);
+
+        let entries = if content.is_empty() {
+            vec![]
+        } else {
+            body.split('\n')
+                .map(|raw| TodoEntry {
+                    line: RebaseTodoLine::parse(raw, comment),
+                    raw: Some(raw.to_string()),
+                })
+                .collect()
+        };
+
+        RebaseTodo {
+            entries,
+            crlf: content.contains("\r\n"),
+            trailing_newline,
+        }
+    }
+
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &RebaseTodoLine> + ExactSizeIterator {
+        self.entries.iter().map(|entry| &entry.line)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&RebaseTodoLine> {
+        self.entries.get(idx).map(|entry| &entry.line)
+    }
+
+    /// Replace a line. Setting a line to what it already is keeps its
+    /// original text.
+    pub fn set(&mut self, idx: usize, line: RebaseTodoLine) {
+        let entry = &mut self.entries[idx];
+        if entry.line != line {
+            *entry = TodoEntry { line, raw: None };
+        }
     }
 
-    pub fn lines(&self) -> &Vec<RebaseTodoLine> {
-        &self.lines
+    pub fn insert(&mut self, idx: usize, line: RebaseTodoLine) {
+        self.entries.insert(idx, TodoEntry { line, raw: None });
     }
 
-    pub fn lines_mut(&mut self) -> &mut Vec<RebaseTodoLine> {
-        &mut self.lines
+    /// Insert `exec <command>` after every line that creates a commit, as
+    /// `git rebase -x` does. A run of squashes and fixups gets a single exec
+    /// after its last line, and commits already followed by the same command
+    /// are left alone. Returns the number of lines inserted.
+    pub fn insert_exec_after_commits(&mut self, command: &str) -> usize {
+        let mut inserted = 0;
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            if self.entries[idx].line.creates_commit() {
+                let next = self.entries[idx + 1..]
+                    .iter()
+                    .map(|e| &e.line)
+                    .find(|line| {
+                        !matches!(
+                            line,
+                            RebaseTodoLine::Comment { .. } | RebaseTodoLine::Drop { .. }
+                        )
+                    });
+                let skip = match next {
+                    Some(RebaseTodoLine::Squash { .. } | RebaseTodoLine::Fixup { .. }) => true,
+                    Some(RebaseTodoLine::Exec { command: existing }) => existing == command,
+                    _ => false,
+                };
+
+                if !skip {
+                    idx += 1;
+                    self.insert(
+                        idx,
+                        RebaseTodoLine::Exec {
+                            command: command.to_string(),
+                        },
+                    );
+                    inserted += 1;
+                }
+            }
+            idx += 1;
+        }
+        inserted
+    }
+
+    /// Replace each `reword` of `commit` with a `pick` followed by
+    /// `exec <command>`, so the message is rewritten without git stopping
+    /// to open an editor. Returns whether any line was replaced.
+    pub fn reword_with_exec(&mut self, commit: &str, command: &str) -> bool {
+        let mut replaced = false;
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            if let RebaseTodoLine::Reword { commit: c, rest } = &self.entries[idx].line
+                && c == commit
+            {
+                let pick = RebaseTodoLine::Pick {
+                    commit: c.clone(),
+                    rest: rest.clone(),
+                };
+                self.set(idx, pick);
+                idx += 1;
+                self.insert(
+                    idx,
+                    RebaseTodoLine::Exec {
+                        command: command.to_string(),
+                    },
+                );
+                replaced = true;
+            }
+            idx += 1;
+        }
+        replaced
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.entries.swap(a, b);
+    }
+
+    /// Move the line at `from` to `to`, shifting the lines in between.
+    pub fn move_line(&mut self, from: usize, to: usize) {
+        if from < to {
+            self.entries[from..=to].rotate_left(1);
+        } else {
+            self.entries[to..=from].rotate_right(1);
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) -> RebaseTodoLine {
+        self.entries.remove(idx).line
     }
 }
+
+impl Display for RebaseTodo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match &entry.raw {
+                Some(raw) => write!(f, "{}", raw)?,
+                None if self.crlf => write!(f, "{}\r", entry.line)?,
+                None => write!(f, "{}", entry.line)?,
+            }
+        }
+        if self.trailing_newline {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn parse_comment_hash() {
-        let line = RebaseTodoLine::parse("# this is a comment");
+        let line = RebaseTodoLine::parse("# this is a comment", DEFAULT_COMMENT);
         assert_eq!(format!("{}", line), "# this is a comment");
     }
 
+    #[test]
+    fn parse_configured_comment_marker() {
+        let line = RebaseTodoLine::parse("; Rebase a1b2c3d onto e4f5a6b (2 commands)", ";");
+        assert!(matches!(line, RebaseTodoLine::Comment { .. }));
+        assert_eq!(
+            format!("{}", line),
+            "; Rebase a1b2c3d onto e4f5a6b (2 commands)"
+        );
+
+        let line = RebaseTodoLine::parse("// pick a1b2c3d", "//");
+        assert!(matches!(line, RebaseTodoLine::Comment { .. }));
+    }
+
+    #[test]
+    fn resolve_comment_settings() {
+        assert_eq!(resolve_comment_string(None, None), "#");
+        assert_eq!(resolve_comment_string(None, Some(";".to_string())), ";");
+        assert_eq!(resolve_comment_string(None, Some("auto".to_string())), "#");
+        assert_eq!(
+            resolve_comment_string(Some("//".to_string()), Some(";".to_string())),
+            "//"
+        );
+    }
+
     #[test]
     fn parse_empty_line_is_comment() {
-        let line = RebaseTodoLine::parse("");
+        let line = RebaseTodoLine::parse("", DEFAULT_COMMENT);
         assert_eq!(format!("{}", line), "");
     }
 
     #[test]
     fn parse_pick_and_alias() {
-        let pick = RebaseTodoLine::parse("pick abc123");
+        let pick = RebaseTodoLine::parse("pick abc123", DEFAULT_COMMENT);
         assert_eq!(format!("{}", pick), "pick abc123");
 
-        let alias = RebaseTodoLine::parse("p abc123");
+        let alias = RebaseTodoLine::parse("p abc123", DEFAULT_COMMENT);
         // alias should parse to the canonical "pick" form when displayed
         assert_eq!(format!("{}", alias), "pick abc123");
     }
@@ -299,46 +622,186 @@ mod tests {
             ("f deadbeef", "fixup deadbeef"),
             ("d deadbeef", "drop deadbeef"),
             ("l mylabel", "label mylabel"),
-            ("r mylabel", "reset mylabel"),
+            ("t mylabel", "reset mylabel"),
             ("u refs/heads/main", "update-ref refs/heads/main"),
         ];
 
         for (input, expected) in cases {
-            let parsed = RebaseTodoLine::parse(input);
+            let parsed = RebaseTodoLine::parse(input, DEFAULT_COMMENT);
             assert_eq!(format!("{}", parsed), expected, "input: {}", input);
         }
     }
 
+    #[test]
+    fn parse_fixup_message_flags() {
+        let line = RebaseTodoLine::parse("fixup -C abc123 Better message", DEFAULT_COMMENT);
+        assert!(matches!(
+            line,
+            RebaseTodoLine::Fixup {
+                use_message: true,
+                edit_message: false,
+                ..
+            }
+        ));
+        assert_eq!(line.get_commit(), Some("abc123"));
+        assert_eq!(format!("{}", line), "fixup -C abc123 Better message");
+
+        let line = RebaseTodoLine::parse("f -c abc123", DEFAULT_COMMENT);
+        assert_eq!(format!("{}", line), "fixup -c abc123");
+
+        let line = RebaseTodoLine::parse("fixup abc123 -c is not a flag here", DEFAULT_COMMENT);
+        assert_eq!(format!("{}", line), "fixup abc123 -c is not a flag here");
+    }
+
+    #[test]
+    fn message_text_is_never_parsed_as_flags() {
+        let cases = [
+            "pick abc -v fix",
+            "reword abc --help me",
+            "pick abc -h",
+            "pick abc -- fix",
+            "label onto --force",
+            "merge -C abc topic -- --help",
+        ];
+
+        for input in cases {
+            let parsed = RebaseTodoLine::parse(input, DEFAULT_COMMENT);
+            assert!(
+                !matches!(parsed, RebaseTodoLine::Comment { .. }),
+                "input: {}",
+                input
+            );
+            assert_eq!(format!("{}", parsed), input);
+        }
+    }
+
+    #[test]
+    fn parse_break_and_noop() {
+        let line = RebaseTodoLine::parse("b", DEFAULT_COMMENT);
+        assert_eq!(line, RebaseTodoLine::Break);
+        assert_eq!(format!("{}", line), "break");
+
+        let line = RebaseTodoLine::parse("noop", DEFAULT_COMMENT);
+        assert_eq!(line, RebaseTodoLine::Noop);
+        assert_eq!(format!("{}", line), "noop");
+    }
+
+    #[test]
+    fn inserted_lines_use_file_line_endings() {
+        let mut todo = RebaseTodo::parse("pick a1b2c3d  First\r\n", DEFAULT_COMMENT);
+        todo.insert(1, RebaseTodoLine::Break);
+        assert_eq!(todo.to_string(), "pick a1b2c3d  First\r\nbreak\r\n");
+    }
+
+    #[test]
+    fn exec_after_every_commit() {
+        let content = "pick a1 First\nfixup b2 Fix first\ndrop c3 Dropped\npick d4 Second\nexec make\npick e5 Third\n\n# Commands:\n";
+        let mut todo = RebaseTodo::parse(content, DEFAULT_COMMENT);
+        assert_eq!(todo.insert_exec_after_commits("make"), 2);
+        assert_eq!(
+            todo.to_string(),
+            "pick a1 First\nfixup b2 Fix first\nexec make\ndrop c3 Dropped\npick d4 Second\nexec make\npick e5 Third\nexec make\n\n# Commands:\n"
+        );
+
+        // Running it again is a no-op
+        assert_eq!(todo.insert_exec_after_commits("make"), 0);
+    }
+
+    #[test]
+    fn reword_becomes_pick_and_exec() {
+        let mut todo = RebaseTodo::parse(
+            "reword a1 First\npick b2 Second\nreword c3 Third\n",
+            DEFAULT_COMMENT,
+        );
+        assert!(todo.reword_with_exec("a1", "git commit --amend -F msg"));
+        assert!(!todo.reword_with_exec("b2", "git commit --amend -F msg"));
+        assert_eq!(
+            todo.to_string(),
+            "pick a1 First\nexec git commit --amend -F msg\npick b2 Second\nreword c3 Third\n"
+        );
+    }
+
+    #[test]
+    fn parse_sequencer_revert() {
+        let line = RebaseTodoLine::parse("revert 36be240 Merge branch 'feature'", DEFAULT_COMMENT);
+        assert!(matches!(line, RebaseTodoLine::Revert { .. }));
+        assert_eq!(line.get_commit(), Some("36be240"));
+        assert_eq!(format!("{}", line), "revert 36be240 Merge branch 'feature'");
+    }
+
     #[test]
     fn parse_exec_with_multiple_args() {
-        let line = RebaseTodoLine::parse("exec echo hello world");
+        let line = RebaseTodoLine::parse("exec echo hello world", DEFAULT_COMMENT);
         assert_eq!(format!("{}", line), "exec echo hello world");
-        let alias = RebaseTodoLine::parse("x echo hello world");
+        let alias = RebaseTodoLine::parse("x echo hello world", DEFAULT_COMMENT);
         assert_eq!(format!("{}", alias), "exec echo hello world");
     }
 
+    #[test]
+    fn parse_exec_keeps_command_verbatim() {
+        let line =
+            RebaseTodoLine::parse(r#"x   make test ARGS="a  b" -- 'c   d'"#, DEFAULT_COMMENT);
+        assert_eq!(
+            line,
+            RebaseTodoLine::Exec {
+                command: r#"make test ARGS="a  b" -- 'c   d'"#.to_string()
+            }
+        );
+        assert_eq!(
+            format!("{}", line),
+            r#"exec make test ARGS="a  b" -- 'c   d'"#
+        );
+    }
+
     #[test]
     fn parse_merge_with_and_without_commit_flag() {
-        let without = RebaseTodoLine::parse("merge feature_branch");
+        let without = RebaseTodoLine::parse("merge feature_branch", DEFAULT_COMMENT);
         assert_eq!(format!("{}", without), "merge feature_branch");
 
-        let with_c = RebaseTodoLine::parse("merge -c abc123 feature_branch");
+        let with_c = RebaseTodoLine::parse("merge -c abc123 feature_branch", DEFAULT_COMMENT);
         assert_eq!(format!("{}", with_c), "merge -c abc123 feature_branch");
 
         // alias 'm' should behave like 'merge'
-        let alias = RebaseTodoLine::parse("m -c abc123 feature_branch");
+        let alias = RebaseTodoLine::parse("m -c abc123 feature_branch", DEFAULT_COMMENT);
         assert_eq!(format!("{}", alias), "merge -c abc123 feature_branch");
     }
 
+    #[test]
+    fn parse_merge_as_written_by_rebase_merges() {
+        let line = RebaseTodoLine::parse(
+            "merge -C 674bbf5 feat # Merge branch 'feat' -c",
+            DEFAULT_COMMENT,
+        );
+        assert_eq!(
+            line,
+            RebaseTodoLine::Merge {
+                commit: Some("674bbf5".into()),
+                edit_commit: None,
+                label: "feat".into(),
+                rest: vec![
+                    "#".into(),
+                    "Merge".into(),
+                    "branch".into(),
+                    "'feat'".into(),
+                    "-c".into()
+                ],
+            }
+        );
+        assert_eq!(line.get_merge_labels(), vec!["feat"]);
+        assert_eq!(
+            format!("{}", line),
+            "merge -C 674bbf5 feat # Merge branch 'feat' -c"
+        );
+
+        let octopus = RebaseTodoLine::parse("merge -C abc a b # Octopus", DEFAULT_COMMENT);
+        assert_eq!(octopus.get_merge_labels(), vec!["a", "b"]);
+    }
+
     #[test]
     fn parse_rebase_todo_multiple_lines() {
         let content = "# top comment\npick a1b2c3d\ndrop deadbeef\n\nexec echo hi\n";
-        let todo = RebaseTodo::parse(content);
-        let rendered = todo
-            .lines()
-            .iter()
-            .map(|l| l.to_string())
-            .collect::<Vec<_>>();
+        let todo = RebaseTodo::parse(content, DEFAULT_COMMENT);
+        let rendered = todo.lines().map(|l| l.to_string()).collect::<Vec<_>>();
         assert_eq!(
             rendered,
             vec![
@@ -350,4 +813,99 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn unedited_todo_is_written_verbatim() {
+        let content =
+            "pick  a1b2c3d   First commit\r\n  # indented comment\r\nexec make   test\r\n";
+        assert_eq!(
+            RebaseTodo::parse(content, DEFAULT_COMMENT).to_string(),
+            content
+        );
+        assert_eq!(
+            RebaseTodo::parse("pick a1b2c3d", DEFAULT_COMMENT).to_string(),
+            "pick a1b2c3d"
+        );
+        let content = "pick abc -v fix\nreword abc --help me\npick abc -h\npick abc -- fix\n";
+        assert_eq!(
+            RebaseTodo::parse(content, DEFAULT_COMMENT).to_string(),
+            content
+        );
+        assert_eq!(RebaseTodo::parse("", DEFAULT_COMMENT).to_string(), "");
+        assert_eq!(
+            RebaseTodo::parse("\n\n", DEFAULT_COMMENT).to_string(),
+            "\n\n"
+        );
+    }
+
+    #[test]
+    fn only_changed_lines_are_reserialized() {
+        let content = "pick  a1b2c3d   First\r\npick e4f5a6b  Second\r\n";
+        let mut todo = RebaseTodo::parse(content, DEFAULT_COMMENT);
+
+        let edit = match todo.get(1) {
+            Some(RebaseTodoLine::Pick { commit, rest }) => RebaseTodoLine::Edit {
+                commit: commit.clone(),
+                rest: rest.clone(),
+            },
+            _ => unreachable!(),
+        };
+        todo.set(1, edit);
+        todo.swap(0, 1);
+
+        assert_eq!(
+            todo.to_string(),
+            "edit e4f5a6b Second\r\npick  a1b2c3d   First\r\n"
+        );
+    }
+
+    #[test]
+    fn move_line_shifts_lines_between() {
+        let mut todo = RebaseTodo::parse(
+            "pick a First\npick b  Second\npick c Third\n",
+            DEFAULT_COMMENT,
+        );
+
+        todo.move_line(2, 0);
+        assert_eq!(
+            todo.to_string(),
+            "pick c Third\npick a First\npick b  Second\n"
+        );
+
+        todo.move_line(0, 2);
+        assert_eq!(
+            todo.to_string(),
+            "pick a First\npick b  Second\npick c Third\n"
+        );
+    }
+
+    #[test]
+    fn setting_the_same_line_keeps_raw_text() {
+        let mut todo = RebaseTodo::parse("p   a1b2c3d  First\n", DEFAULT_COMMENT);
+        let line = todo.get(0).unwrap().clone();
+        todo.set(0, line);
+        assert_eq!(todo.to_string(), "p   a1b2c3d  First\n");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn parse_then_save_is_byte_identical(
+            lines in proptest::collection::vec(
+                "(pick|p|edit|fixup|squash|exec|x|drop|label|reset|merge -C|update-ref|#|)[ \t]{0,3}[0-9a-f]{0,8}[ \t]{0,3}[ -~]{0,20}\r?",
+                0..20,
+            ),
+            trailing_newline in proptest::bool::ANY,
+        ) {
+            let mut content = lines.join("\n");
+            if trailing_newline {
+                content.push('\n');
+            }
+            proptest::prop_assert_eq!(RebaseTodo::parse(&content, DEFAULT_COMMENT).to_string(), content);
+        }
+
+        #[test]
+        fn arbitrary_text_round_trips(content in "(?s).{0,200}") {
+            proptest::prop_assert_eq!(RebaseTodo::parse(&content, DEFAULT_COMMENT).to_string(), content);
+        }
+    }
 }
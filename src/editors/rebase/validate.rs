@@ -0,0 +1,360 @@
+use std::collections::{HashMap, HashSet};
+
+use git2::Repository;
+use ratatui::style::{Color, Style};
+
+use crate::editors::rebase::todo::{RebaseTodo, RebaseTodoLine};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// git will certainly stop with an error
+    Error,
+    /// Probably a mistake, but git will run it
+    Warning,
+}
+
+impl Severity {
+    pub fn get_label(&self) -> &str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    pub fn get_style(&self) -> Style {
+        match self {
+            Severity::Error => Style::default().fg(Color::Red),
+            Severity::Warning => Style::default().fg(Color::Yellow),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Index of the todo line
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(line: usize, message: String) -> Self {
+        Problem {
+            line,
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(line: usize, message: String) -> Self {
+        Problem {
+            line,
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+/// Check a todo for mistakes that git would only report once the rebase is
+/// running. `resolves` says whether a name that is not a label is a
+/// revision, since `reset` and `merge` fall back to those.
+pub fn validate(todo: &RebaseTodo, comment: &str, resolves: impl Fn(&str) -> bool) -> Vec<Problem> {
+    let labels = todo
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| match line {
+            RebaseTodoLine::Label { label, .. } => Some((label.as_str(), idx)),
+            _ => None,
+        })
+        .fold(
+            HashMap::<&str, Vec<usize>>::new(),
+            |mut labels, (label, idx)| {
+                labels.entry(label).or_default().push(idx);
+                labels
+            },
+        );
+
+    // A reset or merge must name a label made by an earlier line
+    let check_label = |idx: usize, label: &str| -> Option<Problem> {
+        let defined = labels.get(label).map(Vec::as_slice).unwrap_or_default();
+        if defined.iter().any(|&line| line < idx) || resolves(label) {
+            None
+        } else if let Some(line) = defined.first() {
+            Some(Problem::error(
+                idx,
+                format!("'{}' is not labelled until line {}", label, line + 1),
+            ))
+        } else {
+            Some(Problem::error(
+                idx,
+                format!("'{}' is never labelled", label),
+            ))
+        }
+    };
+
+    let mut problems = vec![];
+    let mut seen_command = false;
+    let mut seen_commit = false;
+    let mut picked = HashSet::new();
+
+    for (idx, line) in todo.lines().enumerate() {
+        match line {
+            RebaseTodoLine::Comment { message } => {
+                // git stops the rebase with "invalid line" instead
+                if !message.trim().is_empty() && !message.trim_start().starts_with(comment) {
+                    problems.push(Problem::error(
+                        idx,
+                        "not a todo command, git will reject this line".to_string(),
+                    ));
+                }
+                continue;
+            }
+            RebaseTodoLine::Squash { .. } | RebaseTodoLine::Fixup { .. } if !seen_command => {
+                problems.push(Problem::error(
+                    idx,
+                    format!("cannot {} without a previous commit", line.get_action()),
+                ));
+            }
+            RebaseTodoLine::Squash { .. } | RebaseTodoLine::Fixup { .. } if !seen_commit => {
+                problems.push(Problem::warning(
+                    idx,
+                    format!(
+                        "{} melds into a commit that is not being rebased",
+                        line.get_action()
+                    ),
+                ));
+            }
+            RebaseTodoLine::Reset { label, .. } if label != "[new" => {
+                problems.extend(check_label(idx, label));
+            }
+            RebaseTodoLine::Merge { .. } => {
+                problems.extend(
+                    line.get_merge_labels()
+                        .into_iter()
+                        .filter_map(|label| check_label(idx, label)),
+                );
+            }
+            RebaseTodoLine::Label { label, .. } if labels[label.as_str()][0] != idx => {
+                problems.push(Problem::warning(
+                    idx,
+                    format!("'{}' is already labelled", label),
+                ));
+            }
+            _ => {}
+        }
+
+        match line.get_commit() {
+            Some(commit)
+                if !matches!(line, RebaseTodoLine::Drop { .. }) && !picked.insert(commit) =>
+            {
+                problems.push(Problem::warning(
+                    idx,
+                    format!("{} is used more than once", commit),
+                ));
+            }
+            _ => {}
+        }
+
+        // Like git, any command other than a noop or drop gives a later
+        // fixup something to meld into
+        seen_command |= !matches!(line, RebaseTodoLine::Noop | RebaseTodoLine::Drop { .. });
+        seen_commit |= line.creates_commit();
+    }
+
+    let commits = todo
+        .lines()
+        .filter(|line| line.get_commit().is_some())
+        .collect::<Vec<_>>();
+    if !commits.is_empty()
+        && commits
+            .iter()
+            .all(|line| matches!(line, RebaseTodoLine::Drop { .. }))
+    {
+        problems.push(Problem::warning(0, "every commit is dropped".to_string()));
+    }
+
+    problems.sort_by_key(|problem| (problem.line, problem.severity));
+    problems
+}
+
+/// How git treats commit lines that were deleted from the todo, from
+/// `rebase.missingCommitsCheck`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingCommitsCheck {
+    #[default]
+    Ignore,
+    Warn,
+    Error,
+}
+
+impl MissingCommitsCheck {
+    pub fn from_repo(repo: &Repository) -> Self {
+        let value = repo
+            .config()
+            .ok()
+            .and_then(|config| config.get_string("rebase.missingCommitsCheck").ok());
+        Self::parse(value.as_deref())
+    }
+
+    /// git ignores unknown values after warning about them.
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            Some("warn") => MissingCommitsCheck::Warn,
+            Some("error") => MissingCommitsCheck::Error,
+            _ => MissingCommitsCheck::Ignore,
+        }
+    }
+
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            MissingCommitsCheck::Ignore => None,
+            MissingCommitsCheck::Warn => Some(Severity::Warning),
+            MissingCommitsCheck::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// Commit lines of the original todo whose commit is no longer in the todo.
+/// Dropped commits are not missing, since git only checks for deleted lines.
+pub fn missing_commits<'a>(
+    original: &'a [RebaseTodoLine],
+    todo: &RebaseTodo,
+) -> Vec<&'a RebaseTodoLine> {
+    let present = todo
+        .lines()
+        .filter_map(|line| line.get_commit())
+        .collect::<HashSet<_>>();
+
+    original
+        .iter()
+        .filter(|line| {
+            line.get_commit()
+                .is_some_and(|commit| !present.contains(commit))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editors::rebase::todo::DEFAULT_COMMENT;
+
+    fn check(content: &str) -> Vec<(usize, Severity, String)> {
+        let todo = RebaseTodo::parse(content, DEFAULT_COMMENT);
+        validate(&todo, DEFAULT_COMMENT, |name| name == "main")
+            .into_iter()
+            .map(|problem| (problem.line, problem.severity, problem.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid_todo_has_no_problems() {
+        let content = "label onto\n\nreset onto\npick a One\nlabel topic\n\nreset onto\nmerge -C b topic # Merge\nfixup c Fix\nreset main\n";
+        assert_eq!(check(content), vec![]);
+    }
+
+    #[test]
+    fn fixup_needs_a_previous_commit() {
+        assert_eq!(
+            check("# comment\ndrop a One\nsquash b Two\npick c Three\n"),
+            vec![(
+                2,
+                Severity::Error,
+                "cannot squash without a previous commit".to_string()
+            )]
+        );
+        assert_eq!(
+            check("exec make\nfixup a One\n"),
+            vec![(
+                1,
+                Severity::Warning,
+                "fixup melds into a commit that is not being rebased".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn labels_must_exist_before_use() {
+        assert_eq!(
+            check("reset onto\npick a One\nlabel onto\nmerge missing main\n"),
+            vec![
+                (
+                    0,
+                    Severity::Error,
+                    "'onto' is not labelled until line 3".to_string()
+                ),
+                (
+                    3,
+                    Severity::Error,
+                    "'missing' is never labelled".to_string()
+                ),
+            ]
+        );
+        assert_eq!(check("reset [new root]\npick a One\n"), vec![]);
+    }
+
+    #[test]
+    fn unparsable_lines_are_errors() {
+        assert_eq!(
+            check(
+                "pick a One\nbogus line\n  # indented comment\n   \n\
+                 pick b -v fix\nreword c --help me\npick d -h\npick e -- fix\n"
+            ),
+            vec![(
+                1,
+                Severity::Error,
+                "not a todo command, git will reject this line".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn warnings_for_likely_mistakes() {
+        assert_eq!(
+            check("pick a One\npick a One\nlabel x\nlabel x\n"),
+            vec![
+                (1, Severity::Warning, "a is used more than once".to_string()),
+                (3, Severity::Warning, "'x' is already labelled".to_string()),
+            ]
+        );
+        assert_eq!(
+            check("drop a One\ndrop b Two\n"),
+            vec![(0, Severity::Warning, "every commit is dropped".to_string())]
+        );
+    }
+
+    #[test]
+    fn missing_commits_check_from_config() {
+        assert_eq!(
+            MissingCommitsCheck::parse(None),
+            MissingCommitsCheck::Ignore
+        );
+        assert_eq!(
+            MissingCommitsCheck::parse(Some("Warn")),
+            MissingCommitsCheck::Warn
+        );
+        assert_eq!(
+            MissingCommitsCheck::parse(Some("error")),
+            MissingCommitsCheck::Error
+        );
+        assert_eq!(
+            MissingCommitsCheck::parse(Some("bogus")),
+            MissingCommitsCheck::Ignore
+        );
+    }
+
+    #[test]
+    fn deleted_commits_are_missing_but_dropped_ones_are_not() {
+        let original = RebaseTodo::parse("pick a One\npick b Two\npick c Three\n", DEFAULT_COMMENT)
+            .lines()
+            .cloned()
+            .collect::<Vec<_>>();
+        let todo = RebaseTodo::parse("drop a One\nexec make\n", DEFAULT_COMMENT);
+
+        let missing = missing_commits(&original, &todo)
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, vec!["pick b Two", "pick c Three"]);
+    }
+}
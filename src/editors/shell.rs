@@ -0,0 +1,225 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// The program a simple command runs
+    Command,
+    Word,
+    /// A `NAME=value` prefix before the command
+    Assignment,
+    SingleQuoted,
+    DoubleQuoted,
+    Variable,
+    Operator,
+    Comment,
+    Whitespace,
+}
+
+impl TokenKind {
+    pub fn get_style(&self) -> Style {
+        match self {
+            TokenKind::Command => Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Word | TokenKind::Whitespace => Style::default(),
+            TokenKind::Assignment => Style::default().fg(Color::Cyan),
+            TokenKind::SingleQuoted => Style::default().fg(Color::Green),
+            TokenKind::DoubleQuoted => Style::default().fg(Color::Yellow),
+            TokenKind::Variable => Style::default().fg(Color::Magenta),
+            TokenKind::Operator => Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Comment => Style::default().add_modifier(Modifier::DIM),
+        }
+    }
+}
+
+/// Operators, longest first so `&&` wins over `&`.
+const OPERATORS: &[&str] = &[
+    "&&", "||", ";;", ">>", "$(", "|", ";", "&", ">", "<", "(", ")",
+];
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace()
+        && !matches!(
+            c,
+            '\'' | '"' | '$' | '|' | ';' | '&' | '>' | '<' | '(' | ')'
+        )
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// Split a shell command into highlightable tokens. This is not a full shell
+/// parser, but every byte of the input ends up in exactly one token, so the
+/// tokens always join back into the original command.
+pub fn tokenize(command: &str) -> Vec<(TokenKind, &str)> {
+    let mut tokens = vec![];
+    let mut expect_command = true;
+    let mut rest = command;
+
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = if c.is_whitespace() {
+            let len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            (TokenKind::Whitespace, len)
+        } else if c == '#' {
+            (TokenKind::Comment, rest.len())
+        } else if c == '\'' {
+            let len = rest[1..].find('\'').map_or(rest.len(), |i| i + 2);
+            (TokenKind::SingleQuoted, len)
+        } else if c == '"' {
+            let mut escaped = false;
+            let end = rest[1..].char_indices().find(|&(_, c)| {
+                let close = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                close
+            });
+            (
+                TokenKind::DoubleQuoted,
+                end.map_or(rest.len(), |(i, _)| i + 2),
+            )
+        } else if let Some(braced) = rest.strip_prefix("${") {
+            let len = braced.find('}').map_or(rest.len(), |i| i + 3);
+            (TokenKind::Variable, len)
+        } else if let Some(operator) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            (TokenKind::Operator, operator.len())
+        } else if c == '$' {
+            let name = rest[1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len() - 1);
+            // Special parameters such as `$?` and `$@` are a single character
+            let len = if name == 0 {
+                rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8())
+            } else {
+                name + 1
+            };
+            (TokenKind::Variable, len)
+        } else {
+            let mut escaped = false;
+            let len = rest
+                .char_indices()
+                .find(|&(_, c)| {
+                    let end = !escaped && !is_word_char(c);
+                    escaped = c == '\\' && !escaped;
+                    end
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            let word = &rest[..len];
+            let kind = if !expect_command {
+                TokenKind::Word
+            } else if is_assignment(word) {
+                TokenKind::Assignment
+            } else {
+                TokenKind::Command
+            };
+            (kind, len)
+        };
+
+        let (token, remaining) = rest.split_at(len);
+        match kind {
+            TokenKind::Operator => expect_command = !matches!(token, ">" | ">>" | "<" | ")"),
+            TokenKind::Command => expect_command = false,
+            TokenKind::SingleQuoted | TokenKind::DoubleQuoted | TokenKind::Variable => {
+                expect_command = false
+            }
+            _ => {}
+        }
+
+        tokens.push((kind, token));
+        rest = remaining;
+    }
+
+    tokens
+}
+
+/// A command as a highlighted line.
+pub fn highlight(command: &str) -> Line<'static> {
+    Line::from(
+        tokenize(command)
+            .into_iter()
+            .map(|(kind, token)| Span::styled(token.to_string(), kind.get_style()))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Quote a string so `sh` reads it as a single word.
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_join_back_into_the_command() {
+        let command =
+            r#"FOO=1 make test ARGS="a  b \" c" && echo ${HOME}/$USER $? | tee 'o u t' # done"#;
+        let joined = tokenize(command)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect::<String>();
+        assert_eq!(joined, command);
+    }
+
+    #[test]
+    fn tokenize_command_line() {
+        let tokens = tokenize(r#"FOO=1 make ARGS="a  b" && echo $HOME>out"#)
+            .into_iter()
+            .filter(|(kind, _)| *kind != TokenKind::Whitespace)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Assignment, "FOO=1"),
+                (TokenKind::Command, "make"),
+                (TokenKind::Word, "ARGS="),
+                (TokenKind::DoubleQuoted, "\"a  b\""),
+                (TokenKind::Operator, "&&"),
+                (TokenKind::Command, "echo"),
+                (TokenKind::Variable, "$HOME"),
+                (TokenKind::Operator, ">"),
+                (TokenKind::Word, "out"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_comment_and_unterminated_quote() {
+        assert_eq!(
+            tokenize("ls # list"),
+            vec![
+                (TokenKind::Command, "ls"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Comment, "# list"),
+            ]
+        );
+        assert_eq!(
+            tokenize("echo 'oops"),
+            vec![
+                (TokenKind::Command, "echo"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::SingleQuoted, "'oops"),
+            ]
+        );
+    }
+
+    #[test]
+    fn quote_for_shell() {
+        assert_eq!(quote("/usr/bin/glitt"), "'/usr/bin/glitt'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
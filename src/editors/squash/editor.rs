@@ -0,0 +1,401 @@
+use crate::editors::{
+    Editor,
+    message::CommitMessage,
+    rebase::todo::{DEFAULT_COMMENT, RebaseTodo, RebaseTodoLine, comment_string},
+    squash::message::{SquashMessage, SquashSection},
+};
+use git2::Repository;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+/// A constituent commit message along with the todo line that produced it.
+pub struct SquashBlock {
+    section: SquashSection,
+    line: RebaseTodoLine,
+    keep: bool,
+}
+
+impl SquashBlock {
+    /// The todo line this block is displayed as, so dropped blocks look like
+    /// dropped commits in the rebase editor.
+    fn display_line(&self) -> RebaseTodoLine {
+        if self.keep {
+            self.line.clone()
+        } else {
+            RebaseTodoLine::Drop {
+                commit: self.line.get_commit().unwrap_or_default().to_string(),
+                rest: vec![],
+            }
+        }
+    }
+}
+
+pub struct SquashEditor {
+    path: PathBuf,
+    comment: String,
+    comments: Vec<String>,
+    blocks: Vec<SquashBlock>,
+    /// The section number whose message is used as the subject
+    subject: Option<usize>,
+    /// Set when saving was refused because every message is dropped
+    save_blocked: bool,
+    list_state: ListState,
+}
+
+impl SquashEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let comment = Self::get_comment_string(&path);
+        let message = SquashMessage::parse(&content, &comment)
+            .filter(|message| !message.sections.is_empty())
+            .ok_or_else(|| color_eyre::eyre::eyre!("Not a squash message"))?;
+
+        let lines = Self::get_done_lines(&path, message.sections.len(), &comment);
+        let blocks = message
+            .sections
+            .into_iter()
+            .enumerate()
+            .map(|(i, section)| {
+                let line = lines
+                    .as_ref()
+                    .map(|lines| lines[i].clone())
+                    .unwrap_or_else(|| Self::infer_line(&section));
+                SquashBlock {
+                    keep: !section.skipped,
+                    section,
+                    line,
+                }
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        Ok(Self {
+            path,
+            comment,
+            comments: message.comments,
+            blocks,
+            subject: None,
+            save_blocked: false,
+            list_state,
+        })
+    }
+
+    fn get_comment_string(path: &Path) -> String {
+        path.parent()
+            .and_then(|git_dir| Repository::discover(git_dir).ok())
+            .map_or_else(|| DEFAULT_COMMENT.to_string(), |repo| comment_string(&repo))
+    }
+
+    /// The last `count` lines git has executed that made a commit, which are
+    /// the commits being combined.
+    fn get_done_lines(path: &Path, count: usize, comment: &str) -> Option<Vec<RebaseTodoLine>> {
+        let git_dir = path.parent()?;
+        let content = std::fs::read_to_string(git_dir.join("rebase-merge").join("done")).ok()?;
+        let todo = RebaseTodo::parse(&content, comment);
+
+        let lines = todo
+            .lines()
+            .filter(|line| line.creates_commit())
+            .cloned()
+            .collect::<Vec<_>>();
+        if lines.len() < count {
+            return None;
+        }
+
+        Some(lines[lines.len() - count..].to_vec())
+    }
+
+    /// Without the rebase state, infer the command from the section: the
+    /// first commit was picked, skipped messages were fixups.
+    fn infer_line(section: &SquashSection) -> RebaseTodoLine {
+        let commit = String::new();
+        let rest = vec![section.subject().to_string()];
+        if section.number == 1 {
+            RebaseTodoLine::Pick { commit, rest }
+        } else if section.skipped {
+            RebaseTodoLine::Fixup {
+                use_message: false,
+                edit_message: false,
+                commit,
+                rest,
+            }
+        } else {
+            RebaseTodoLine::Squash { commit, rest }
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    pub fn move_cursor_down(&mut self) {
+        let idx = (self.selected() + 1) % self.blocks.len();
+        self.list_state.select(Some(idx));
+    }
+
+    pub fn move_cursor_up(&mut self) {
+        let len = self.blocks.len();
+        let idx = (self.selected() + len - 1) % len;
+        self.list_state.select(Some(idx));
+    }
+
+    pub fn swap_down(&mut self) {
+        let current = self.selected();
+        let idx = (current + 1) % self.blocks.len();
+        self.blocks.swap(current, idx);
+        self.list_state.select(Some(idx));
+    }
+
+    pub fn swap_up(&mut self) {
+        let len = self.blocks.len();
+        let current = self.selected();
+        let idx = (current + len - 1) % len;
+        self.blocks.swap(current, idx);
+        self.list_state.select(Some(idx));
+    }
+
+    pub fn set_keep(&mut self, keep: bool) {
+        let idx = self.selected();
+        self.blocks[idx].keep = keep;
+    }
+
+    pub fn toggle_keep(&mut self) {
+        let idx = self.selected();
+        self.blocks[idx].keep = !self.blocks[idx].keep;
+    }
+
+    /// Use the selected block as the subject, or clear it if it already is.
+    pub fn toggle_subject(&mut self) {
+        let idx = self.selected();
+        let block = &mut self.blocks[idx];
+        let number = block.section.number;
+        if self.subject == Some(number) {
+            self.subject = None;
+        } else {
+            block.keep = true;
+            self.subject = Some(number);
+        }
+    }
+
+    /// The combined message: the subject block first, followed by every
+    /// other kept block in order.
+    pub fn combined_message(&self) -> CommitMessage {
+        let subject = self
+            .blocks
+            .iter()
+            .filter(|block| block.keep && Some(block.section.number) == self.subject);
+        let rest = self
+            .blocks
+            .iter()
+            .filter(|block| block.keep && Some(block.section.number) != self.subject);
+
+        let text = subject
+            .chain(rest)
+            .map(|block| block.section.message.as_str())
+            .filter(|message| !message.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        CommitMessage {
+            comments: self.comments.clone(),
+            ..CommitMessage::parse(&text, &self.comment)
+        }
+    }
+
+    fn has_kept_blocks(&self) -> bool {
+        self.blocks.iter().any(|block| block.keep)
+    }
+
+    /// Save unless every message is dropped, since the empty message would
+    /// abort the commit. Returns whether the message was saved.
+    pub fn try_save(&mut self) -> Result<bool, color_eyre::Report> {
+        if !self.has_kept_blocks() {
+            self.save_blocked = true;
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn save(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, self.combined_message().to_string())?;
+        Ok(())
+    }
+
+    /// An empty message makes git abort the commit.
+    pub fn save_empty(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    pub fn render_blocks(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = Block::default().title("Messages").borders(Borders::ALL);
+        let selected = self.selected();
+
+        let items: Vec<ListItem> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let line = block.display_line();
+                let header_style = if i == selected {
+                    line.get_selected_style()
+                } else {
+                    line.get_style()
+                };
+
+                let mut header = format!("{} {}", line.get_action(), block.section.number);
+                if let Some(commit) = line.get_commit().filter(|c| !c.is_empty()) {
+                    header.push_str(&format!(" {}", commit));
+                }
+                if self.subject == Some(block.section.number) {
+                    header.push_str(" (subject)");
+                }
+
+                let mut lines = vec![Line::from(header).style(header_style)];
+                lines.extend(
+                    block
+                        .section
+                        .message
+                        .lines()
+                        .map(|l| Line::from(format!("  {}", l)).style(line.get_style())),
+                );
+                lines.push(Line::from(""));
+
+                ListItem::new(lines)
+            })
+            .collect();
+
+        let list = List::new(items).block(block);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    pub fn render_preview(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let block = if self.save_blocked && !self.has_kept_blocks() {
+            Block::default()
+                .title("Result: keep a message, or press a to abort the commit")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+        } else {
+            Block::default().title("Result").borders(Borders::ALL)
+        };
+        let paragraph = Paragraph::new(self.combined_message().text()).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Move  {} Reorder  {} toggle  {} keep  {} drop  {} take as subject  {} quit and save  {} abort",
+            "↑/↓".bold(),
+            "⇧↑/⇧↓".bold(),
+            "space".bold(),
+            "k".bold(),
+            "d".bold(),
+            "t".bold(),
+            "q".bold(),
+            "a".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for SquashEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("COMMIT_EDITMSG"))
+            && std::fs::read_to_string(path).is_ok_and(|content| {
+                // Without any sections there is nothing to combine, so leave
+                // the message to the commit editor
+                SquashMessage::parse(&content, &Self::get_comment_string(path))
+                    .is_some_and(|message| !message.sections.is_empty())
+            })
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.render_blocks(frame, editor_area[0]);
+        self.render_preview(frame, editor_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    modifiers: KeyModifiers::SHIFT,
+                    ..
+                }) => self.swap_down(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }) => self.move_cursor_down(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up,
+                    modifiers: KeyModifiers::SHIFT,
+                    ..
+                }) => self.swap_up(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => self.move_cursor_up(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(' '),
+                    ..
+                }) => self.toggle_keep(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('k'),
+                    ..
+                }) => self.set_keep(true),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('d'),
+                    ..
+                }) => self.set_keep(false),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('t'),
+                    ..
+                }) => self.toggle_subject(),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('q'),
+                    ..
+                }) => {
+                    if !self.try_save()? {
+                        continue;
+                    }
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('a'),
+                    ..
+                }) => {
+                    terminal.clear()?;
+                    self.save_empty()?;
+                    return Ok(());
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,213 @@
+/// The first line git writes when it combines commits for a squash, after
+/// the comment string.
+const COMBINATION_HEADER: &str = " This is a combination of ";
+
+/// One constituent commit message of a squash combination.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquashSection {
+    /// 1-based position of the commit in the combination
+    pub number: usize,
+    pub message: String,
+    /// git writes fixup messages commented out, so they are skipped unless
+    /// explicitly kept.
+    pub skipped: bool,
+}
+
+impl SquashSection {
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquashMessage {
+    pub sections: Vec<SquashSection>,
+    pub comments: Vec<String>,
+}
+
+/// Parse a section header such as `# This is the commit message #2:` or
+/// `# The 1st commit message will be skipped:` into its number and whether
+/// it is skipped.
+fn parse_section_header(line: &str, comment: &str) -> Option<(usize, bool)> {
+    let header = line.strip_prefix(comment)?.strip_prefix(' ')?;
+    if !header.contains("commit message") || !header.ends_with(':') {
+        return None;
+    }
+
+    let skipped = if header.starts_with("This is the ") {
+        false
+    } else if header.starts_with("The ") {
+        true
+    } else {
+        return None;
+    };
+
+    let number = header
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|digits| !digits.is_empty())?
+        .parse()
+        .ok()?;
+
+    Some((number, skipped))
+}
+
+fn uncomment<'a>(line: &'a str, comment: &str) -> &'a str {
+    match line.strip_prefix(comment) {
+        Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+        None => line,
+    }
+}
+
+impl SquashMessage {
+    /// Returns `true` if `content` is a squash combination written by git.
+    pub fn is_combination(content: &str, comment: &str) -> bool {
+        content
+            .strip_prefix(comment)
+            .is_some_and(|rest| rest.starts_with(COMBINATION_HEADER))
+    }
+
+    pub fn parse(content: &str, comment: &str) -> Option<Self> {
+        if !Self::is_combination(content, comment) {
+            return None;
+        }
+
+        let mut sections = vec![];
+        let mut comments = vec![];
+
+        // The section being read, its lines, and whether it has ended
+        let mut current: Option<(usize, bool, Vec<&str>, bool)> = None;
+
+        for line in content.lines().skip(1) {
+            if let Some((number, skipped)) = parse_section_header(line, comment) {
+                sections.extend(current.take());
+                current = Some((number, skipped, vec![], false));
+                continue;
+            }
+
+            match &mut current {
+                Some((_, true, lines, done @ false)) => {
+                    if line.starts_with(comment) {
+                        lines.push(uncomment(line, comment));
+                    } else if line.is_empty() && !lines.is_empty() {
+                        *done = true;
+                    }
+                }
+                Some((_, false, lines, done @ false)) => {
+                    if line.starts_with(comment) {
+                        *done = true;
+                        comments.push(line.to_string());
+                    } else {
+                        lines.push(line);
+                    }
+                }
+                _ => {
+                    if line.starts_with(comment) {
+                        comments.push(line.to_string());
+                    }
+                }
+            }
+        }
+        sections.extend(current);
+
+        let sections = sections
+            .into_iter()
+            .map(|(number, skipped, lines, _)| SquashSection {
+                number,
+                message: lines.join("\n").trim().to_string(),
+                skipped,
+            })
+            .collect();
+
+        Some(SquashMessage { sections, comments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "# This is a combination of 3 commits.
+# This is the 1st commit message:
+
+change 1
+
+body of 1
+
+# This is the commit message #2:
+
+change 2
+
+# The commit message #3 will be skipped:
+
+# change 3
+#
+# body of 3
+
+# Please enter the commit message for your changes. Lines starting
+# with '#' will be ignored, and an empty message aborts the commit.
+#
+";
+
+    #[test]
+    fn detects_combination() {
+        assert!(SquashMessage::is_combination(TEMPLATE, "#"));
+        assert!(!SquashMessage::is_combination(TEMPLATE, ";"));
+        assert!(SquashMessage::parse("change 1\n# comment\n", "#").is_none());
+    }
+
+    #[test]
+    fn parse_section_headers() {
+        assert_eq!(
+            parse_section_header("# This is the 1st commit message:", "#"),
+            Some((1, false))
+        );
+        assert_eq!(
+            parse_section_header("# This is the commit message #12:", "#"),
+            Some((12, false))
+        );
+        assert_eq!(
+            parse_section_header("# The commit message #3 will be skipped:", "#"),
+            Some((3, true))
+        );
+        assert_eq!(parse_section_header("# Please enter the commit", "#"), None);
+    }
+
+    #[test]
+    fn parse_combination_sections() {
+        let message = SquashMessage::parse(TEMPLATE, "#").unwrap();
+        assert_eq!(
+            message.sections,
+            vec![
+                SquashSection {
+                    number: 1,
+                    message: "change 1\n\nbody of 1".to_string(),
+                    skipped: false,
+                },
+                SquashSection {
+                    number: 2,
+                    message: "change 2".to_string(),
+                    skipped: false,
+                },
+                SquashSection {
+                    number: 3,
+                    message: "change 3\n\nbody of 3".to_string(),
+                    skipped: true,
+                },
+            ]
+        );
+        assert_eq!(message.comments.len(), 3);
+        assert!(message.comments[0].starts_with("# Please enter"));
+    }
+
+    #[test]
+    fn parse_with_comment_string() {
+        let template = TEMPLATE
+            .replace('#', ";")
+            .replace(";2", "#2")
+            .replace(";3", "#3");
+        let message = SquashMessage::parse(&template, ";").unwrap();
+        assert_eq!(message.sections.len(), 3);
+        assert_eq!(message.sections[2].message, "change 3\n\nbody of 3");
+        assert!(message.comments[0].starts_with("; Please enter"));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod editor;
+pub mod message;
+
+pub use editor::SquashEditor;
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use git2::{Oid, Repository, Sort};
+
+/// Conventional commit types and the heading they are grouped under, in the
+/// order the groups appear in the changelog.
+const COMMIT_TYPES: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "Continuous Integration"),
+    ("style", "Style"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+const OTHER_GROUP: &str = "Other";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    /// By conventional commit type, e.g. `feat:` or `fix(parser):`
+    Type,
+    /// By the top-level directory the commit changes most
+    Path,
+}
+
+/// Split a conventional commit summary such as `feat(ui)!: add thing` into
+/// its type and description.
+pub fn parse_conventional(summary: &str) -> Option<(&str, &str)> {
+    let (prefix, description) = summary.split_once(':')?;
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    Some((kind, description.trim()))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangelogEntry {
+    pub id: String,
+    pub summary: String,
+    /// The top-level directory this commit changes most, `.` for the root
+    pub area: String,
+    pub include: bool,
+}
+
+impl ChangelogEntry {
+    pub fn new(id: &str, summary: &str, area: &str) -> Self {
+        ChangelogEntry {
+            id: id.to_string(),
+            summary: summary.to_string(),
+            area: area.to_string(),
+            include: true,
+        }
+    }
+
+    fn group(&self, grouping: Grouping) -> String {
+        match grouping {
+            Grouping::Type => parse_conventional(&self.summary)
+                .and_then(|(kind, _)| {
+                    COMMIT_TYPES
+                        .iter()
+                        .find(|(t, _)| t.eq_ignore_ascii_case(kind))
+                        .map(|(_, heading)| heading.to_string())
+                })
+                .unwrap_or_else(|| OTHER_GROUP.to_string()),
+            Grouping::Path => self.area.clone(),
+        }
+    }
+
+    /// The text of the entry within its group. Grouping by type already
+    /// states the type, so only the description is kept.
+    pub fn text(&self, grouping: Grouping) -> &str {
+        match (grouping, parse_conventional(&self.summary)) {
+            (Grouping::Type, Some((_, description))) if self.group(grouping) != OTHER_GROUP => {
+                description
+            }
+            _ => &self.summary,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changelog {
+    pub entries: Vec<ChangelogEntry>,
+    pub previous_tag: Option<String>,
+}
+
+impl Changelog {
+    /// Collect the commits from the most recent tag reachable from `HEAD`,
+    /// ignoring `tag_name` since that is the tag being written.
+    pub fn from_repo(repo: &Repository, tag_name: &str) -> Result<Self, git2::Error> {
+        let mut tags = HashMap::new();
+        for name in repo.tag_names(None)?.iter().flatten() {
+            if name == tag_name {
+                continue;
+            }
+            if let Ok(commit) = repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .and_then(|object| object.peel_to_commit())
+            {
+                tags.insert(commit.id(), name.to_string());
+            }
+        }
+
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        walk.push_head()?;
+        let previous = walk
+            .flatten()
+            .find_map(|oid| tags.get(&oid).map(|name| (oid, name.clone())));
+
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        walk.push_head()?;
+        if let Some((oid, _)) = &previous {
+            walk.hide(*oid)?;
+        }
+
+        let mut entries = vec![];
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+
+            let id = commit.as_object().short_id()?;
+            entries.push(ChangelogEntry::new(
+                id.as_str().unwrap_or_default(),
+                commit.summary().unwrap_or_default(),
+                &Self::get_area(repo, commit.id()).unwrap_or_else(|| ".".to_string()),
+            ));
+        }
+
+        Ok(Changelog {
+            entries,
+            previous_tag: previous.map(|(_, name)| name),
+        })
+    }
+
+    /// The top-level directory with the most changed files in a commit.
+    fn get_area(repo: &Repository, oid: Oid) -> Option<String> {
+        let commit = repo.find_commit(oid).ok()?;
+        let tree = commit.tree().ok()?;
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .ok()?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                continue;
+            };
+            let mut components = path.components();
+            let first = components.next()?.as_os_str().to_string_lossy().to_string();
+            let area = if components.next().is_some() {
+                first
+            } else {
+                ".".to_string()
+            };
+            *counts.entry(area).or_default() += 1;
+        }
+
+        counts
+            .into_iter()
+            .max_by(|(a, x), (b, y)| x.cmp(y).then(b.cmp(a)))
+            .map(|(area, _)| area)
+    }
+
+    /// Group titles and the entries in each, in display order.
+    pub fn groups(&self, grouping: Grouping) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = vec![];
+        for (i, entry) in self.entries.iter().enumerate() {
+            let group = entry.group(grouping);
+            match groups.iter_mut().find(|(title, _)| *title == group) {
+                Some((_, entries)) => entries.push(i),
+                None => groups.push((group, vec![i])),
+            }
+        }
+
+        let order = |title: &str| match grouping {
+            Grouping::Type => COMMIT_TYPES
+                .iter()
+                .position(|(_, heading)| *heading == title)
+                .unwrap_or(COMMIT_TYPES.len()),
+            Grouping::Path => 0,
+        };
+        groups.sort_by(|(a, _), (b, _)| match grouping {
+            Grouping::Type => order(a).cmp(&order(b)),
+            Grouping::Path => a.cmp(b),
+        });
+
+        groups
+    }
+
+    /// The included entries as plain text, since `#` headings would be
+    /// stripped from a tag message as comments.
+    pub fn render(&self, grouping: Grouping) -> String {
+        self.groups(grouping)
+            .into_iter()
+            .filter_map(|(title, entries)| {
+                let lines = entries
+                    .into_iter()
+                    .map(|i| &self.entries[i])
+                    .filter(|entry| entry.include)
+                    .map(|entry| format!("- {} ({})", entry.text(grouping), entry.id))
+                    .collect::<Vec<_>>();
+
+                (!lines.is_empty()).then(|| format!("{}:\n{}", title, lines.join("\n")))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changelog() -> Changelog {
+        Changelog {
+            entries: vec![
+                ChangelogEntry::new("a1", "fix(parser): handle empty lines", "src"),
+                ChangelogEntry::new("b2", "feat: add tag editor", "src"),
+                ChangelogEntry::new("c3", "Update README", "."),
+                ChangelogEntry::new("d4", "feat!: drop old config", "docs"),
+            ],
+            previous_tag: Some("v0.1.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn parse_conventional_summaries() {
+        assert_eq!(parse_conventional("feat: add x"), Some(("feat", "add x")));
+        assert_eq!(
+            parse_conventional("fix(ui)!: broken"),
+            Some(("fix", "broken"))
+        );
+        assert_eq!(parse_conventional("Update README"), None);
+        assert_eq!(
+            parse_conventional("Fix: the thing: again"),
+            Some(("Fix", "the thing: again"))
+        );
+        assert_eq!(parse_conventional("see http://x"), None);
+    }
+
+    #[test]
+    fn group_by_type() {
+        let changelog = changelog();
+        assert_eq!(
+            changelog.render(Grouping::Type),
+            "Features:\n- add tag editor (b2)\n- drop old config (d4)\n\nBug Fixes:\n- handle empty lines (a1)\n\nOther:\n- Update README (c3)"
+        );
+    }
+
+    #[test]
+    fn group_by_path() {
+        let changelog = changelog();
+        let groups = changelog.groups(Grouping::Path);
+        assert_eq!(
+            groups,
+            vec![
+                (".".to_string(), vec![2]),
+                ("docs".to_string(), vec![3]),
+                ("src".to_string(), vec![0, 1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn excluded_entries_are_pruned() {
+        let mut changelog = changelog();
+        changelog.entries[0].include = false;
+        changelog.entries[2].include = false;
+        assert_eq!(
+            changelog.render(Grouping::Type),
+            "Features:\n- add tag editor (b2)\n- drop old config (d4)"
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use crate::editors::{
+    Editor,
+    message::{CommitMessage, MessageFocus, MessageInput},
+    rebase::todo::comment_string,
+    tag::changelog::{Changelog, Grouping},
+};
+use git2::Repository;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TagFocus {
+    Message,
+    Changelog,
+}
+
+/// A row of the changelog pane: a group heading or an entry index.
+enum ChangelogRow {
+    Group(String),
+    Entry(usize),
+}
+
+pub struct TagEditor {
+    path: PathBuf,
+    tag_name: String,
+    message: CommitMessage,
+    input: MessageInput,
+    changelog: Changelog,
+    grouping: Grouping,
+    focus: TagFocus,
+    list_state: ListState,
+}
+
+impl TagEditor {
+    pub fn new(path: PathBuf) -> Result<Self, color_eyre::Report> {
+        let content = std::fs::read_to_string(&path)?;
+        let repo = Repository::discover(
+            path.parent()
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?,
+        )?;
+        let comment = comment_string(&repo);
+        let mut message = CommitMessage::parse(&content, &comment);
+        let tag_name = message
+            .comment_after("Write a message for tag:", &comment)
+            .unwrap_or_default()
+            .to_string();
+        if message.subject.is_empty() {
+            message.subject = tag_name.clone();
+        }
+        let input = MessageInput::new(&message);
+
+        let changelog = Changelog::from_repo(&repo, &tag_name).unwrap_or_default();
+
+        let mut editor = Self {
+            path,
+            tag_name,
+            message,
+            input,
+            changelog,
+            grouping: Grouping::Type,
+            focus: TagFocus::Message,
+            list_state: ListState::default(),
+        };
+        editor.select_first_entry();
+
+        Ok(editor)
+    }
+
+    fn rows(&self) -> Vec<ChangelogRow> {
+        self.changelog
+            .groups(self.grouping)
+            .into_iter()
+            .flat_map(|(title, entries)| {
+                std::iter::once(ChangelogRow::Group(title))
+                    .chain(entries.into_iter().map(ChangelogRow::Entry))
+            })
+            .collect()
+    }
+
+    fn select_first_entry(&mut self) {
+        let first = self
+            .rows()
+            .iter()
+            .position(|row| matches!(row, ChangelogRow::Entry(_)));
+        self.list_state.select(first);
+    }
+
+    fn selected_entry(&self) -> Option<usize> {
+        match self.rows().get(self.list_state.selected()?) {
+            Some(ChangelogRow::Entry(idx)) => Some(*idx),
+            _ => None,
+        }
+    }
+
+    pub fn move_cursor_down(&mut self) {
+        let rows = self.rows();
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = (current + 1..rows.len()).find(|&i| matches!(rows[i], ChangelogRow::Entry(_)));
+        if let Some(idx) = next {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    pub fn move_cursor_up(&mut self) {
+        let rows = self.rows();
+        let current = self.list_state.selected().unwrap_or(0);
+        let previous = (0..current)
+            .rev()
+            .find(|&i| matches!(rows[i], ChangelogRow::Entry(_)));
+        if let Some(idx) = previous {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    pub fn toggle_entry(&mut self) {
+        if let Some(idx) = self.selected_entry() {
+            let entry = &mut self.changelog.entries[idx];
+            entry.include = !entry.include;
+        }
+    }
+
+    pub fn toggle_grouping(&mut self) {
+        self.grouping = match self.grouping {
+            Grouping::Type => Grouping::Path,
+            Grouping::Path => Grouping::Type,
+        };
+        self.select_first_entry();
+    }
+
+    /// Cycle subject → body → changelog.
+    pub fn next_focus(&mut self) {
+        match (self.focus, self.input.focus()) {
+            (TagFocus::Message, MessageFocus::Subject) => self.input.set_focus(MessageFocus::Body),
+            (TagFocus::Message, MessageFocus::Body) => self.focus = TagFocus::Changelog,
+            (TagFocus::Changelog, _) => {
+                self.focus = TagFocus::Message;
+                self.input.set_focus(MessageFocus::Subject);
+            }
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), color_eyre::Report> {
+        self.input.apply(&mut self.message);
+
+        let changelog = self.changelog.render(self.grouping);
+        if !changelog.is_empty() {
+            self.message.body = if self.message.body.is_empty() {
+                changelog
+            } else {
+                format!("{}\n\n{}", self.message.body, changelog)
+            };
+        }
+
+        std::fs::write(&self.path, self.message.to_string())?;
+        Ok(())
+    }
+
+    /// An empty message makes git refuse to create the tag.
+    pub fn save_empty(&self) -> Result<(), color_eyre::Report> {
+        std::fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    pub fn render_changelog(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let since = match &self.changelog.previous_tag {
+            Some(tag) => format!("since {}", tag),
+            None => "of all commits".to_string(),
+        };
+        let grouping = match self.grouping {
+            Grouping::Type => "type",
+            Grouping::Path => "path",
+        };
+        let title = format!(
+            "Changelog {} for {} (by {})",
+            since, self.tag_name, grouping
+        );
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(match self.focus {
+                TagFocus::Changelog => Style::default().fg(Color::Blue),
+                TagFocus::Message => Style::default(),
+            });
+
+        let selected = self.list_state.selected();
+        let items: Vec<ListItem> = self
+            .rows()
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| match row {
+                ChangelogRow::Group(title) => {
+                    ListItem::new(Line::from(format!("{}:", title)).bold())
+                }
+                ChangelogRow::Entry(idx) => {
+                    let entry = &self.changelog.entries[idx];
+                    let marker = if entry.include { "[x]" } else { "[ ]" };
+                    let mut style = if entry.include {
+                        Style::default()
+                    } else {
+                        Style::default()
+                            .add_modifier(Modifier::DIM)
+                            .add_modifier(Modifier::CROSSED_OUT)
+                    };
+                    if self.focus == TagFocus::Changelog && selected == Some(i) {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+
+                    let text = format!("  {} {} {}", marker, entry.id, entry.text(self.grouping));
+                    ListItem::new(Line::from(text)).style(style)
+                }
+            })
+            .collect();
+
+        let list = List::new(items).block(block);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    pub fn render_instructions(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let instructions = Paragraph::new(format!(
+            "{} Next field  {} toggle entry  {} group by type/path  {} save and quit  {} abort",
+            "Tab".bold(),
+            "space".bold(),
+            "g".bold(),
+            "^s".bold(),
+            "^c".bold()
+        ))
+        .style(Style::default());
+
+        frame.render_widget(instructions, area);
+    }
+}
+
+impl Editor for TagEditor {
+    fn should_run(path: &Path) -> bool {
+        path.file_name().is_some_and(|f| f.eq("TAG_EDITMSG"))
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        let main_area =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(frame.area());
+
+        self.render_instructions(frame, main_area[0]);
+
+        let editor_area =
+            Layout::horizontal([Constraint::Max(76), Constraint::Fill(1)]).split(main_area[1]);
+
+        self.input
+            .render(frame, editor_area[0], self.focus == TagFocus::Message);
+        self.render_changelog(frame, editor_area[1]);
+    }
+
+    fn run(&mut self, mut terminal: ratatui::DefaultTerminal) -> color_eyre::Result<()> {
+        terminal.clear()?;
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            match (event::read()?, self.focus) {
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('s'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }),
+                    _,
+                ) => {
+                    self.save()?;
+                    terminal.clear()?;
+                    return Ok(());
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('c'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }),
+                    _,
+                ) => {
+                    terminal.clear()?;
+                    self.save_empty()?;
+                    return Ok(());
+                }
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Tab, ..
+                    }),
+                    _,
+                ) => self.next_focus(),
+
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down,
+                        ..
+                    }),
+                    TagFocus::Changelog,
+                ) => self.move_cursor_down(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up, ..
+                    }),
+                    TagFocus::Changelog,
+                ) => self.move_cursor_up(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(' '),
+                        ..
+                    }),
+                    TagFocus::Changelog,
+                ) => self.toggle_entry(),
+                (
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('g'),
+                        ..
+                    }),
+                    TagFocus::Changelog,
+                ) => self.toggle_grouping(),
+
+                (Event::Key(key), TagFocus::Message) => {
+                    self.input.handle_key(key);
+                }
+
+                _ => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod changelog;
+pub mod editor;
+
+pub use editor::TagEditor;
@@ -0,0 +1,241 @@
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Position, Rect},
+    text::Line,
+    widgets::{Block, Paragraph},
+};
+
+/// A minimal multi-line text input.
+///
+/// The cursor column is tracked in characters, not bytes, so that editing
+/// non-ASCII text never splits a code point.
+#[derive(Clone, Debug, Default)]
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    scroll: usize,
+    single_line: bool,
+}
+
+impl TextArea {
+    pub fn new(text: &str) -> Self {
+        let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        Self {
+            lines,
+            ..Default::default()
+        }
+    }
+
+    /// A text area that refuses newlines, for subject lines and prompts.
+    pub fn single_line(text: &str) -> Self {
+        let text = text.lines().next().unwrap_or_default();
+        Self {
+            single_line: true,
+            ..Self::new(text)
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Move the cursor to the end of the text.
+    pub fn move_to_end(&mut self) {
+        self.row = self.lines.len() - 1;
+        self.col = self.line_len(self.row);
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self, row: usize, col: usize) -> usize {
+        self.lines[row]
+            .char_indices()
+            .nth(col)
+            .map(|(i, _)| i)
+            .unwrap_or(self.lines[row].len())
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let idx = self.byte_index(self.row, self.col);
+        self.lines[self.row].insert(idx, c);
+        self.col += 1;
+    }
+
+    pub fn insert_newline(&mut self) {
+        if self.single_line {
+            return;
+        }
+
+        let idx = self.byte_index(self.row, self.col);
+        let rest = self.lines[self.row].split_off(idx);
+        self.row += 1;
+        self.col = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let idx = self.byte_index(self.row, self.col);
+            self.lines[self.row].remove(idx);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let idx = self.byte_index(self.row, self.col);
+            self.lines[self.row].remove(idx);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    /// Apply a key press to the text. Returns `false` if the key is not an
+    /// editing key, so the caller can handle it instead.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Enter if !self.single_line => self.insert_newline(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Up if !self.single_line => self.move_up(),
+            KeyCode::Down if !self.single_line => self.move_down(),
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(self.row),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn render(&mut self, frame: &mut ratatui::Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let height = inner.height.max(1) as usize;
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+
+        let width = inner.width.max(1) as usize;
+        let offset = (self.col + 1).saturating_sub(width);
+
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| Line::from(line.clone()))
+            .collect::<Vec<_>>();
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll as u16, offset as u16));
+        frame.render_widget(paragraph, area);
+
+        if focused {
+            frame.set_cursor_position(Position::new(
+                inner.x + (self.col - offset) as u16,
+                inner.y + (self.row - self.scroll) as u16,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_split_lines() {
+        let mut area = TextArea::new("hello");
+        area.move_to_end();
+        area.insert_newline();
+        for c in "world".chars() {
+            area.insert_char(c);
+        }
+        assert_eq!(area.text(), "hello\nworld");
+    }
+
+    #[test]
+    fn backspace_joins_lines() {
+        let mut area = TextArea::new("ab\ncd");
+        area.move_down();
+        area.backspace();
+        assert_eq!(area.text(), "abcd");
+    }
+
+    #[test]
+    fn single_line_ignores_newlines() {
+        let mut area = TextArea::single_line("subject\nbody");
+        area.move_to_end();
+        area.insert_newline();
+        assert_eq!(area.text(), "subject");
+    }
+
+    #[test]
+    fn edits_multibyte_characters() {
+        let mut area = TextArea::new("héllo");
+        area.move_to_end();
+        area.move_left();
+        area.move_left();
+        area.move_left();
+        area.backspace();
+        assert_eq!(area.text(), "hllo");
+    }
+}
@@ -1,56 +1,81 @@
-use std::process::Command;
+use std::{path::PathBuf, process::Command};
 
 use clap::Parser;
 use color_eyre::Result;
+mod commands;
 mod editors;
 
-use crate::editors::{Editor, rebase::RebaseEditor};
+use crate::commands::rebase::RebaseArgs;
+use crate::editors::{EditorKind, detect_editor, find_editor};
 
-#[derive(Clone, clap::ValueEnum)]
+#[derive(clap::Subcommand)]
 pub enum Commands {
-    Rebase,
+    /// Edit a file, detecting the editor from its path
+    Edit {
+        /// Path to edit
+        path: PathBuf,
+    },
+    /// Run `git rebase` with glitt as the todo editor and stay attached to
+    /// report where it stops
+    Rebase(RebaseArgs),
 }
 
 #[derive(clap::Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Path to edit
-    path: std::path::PathBuf,
+    path: Option<PathBuf>,
+
+    /// Use a specific editor instead of detecting one from the path.
+    #[clap(long, global = true)]
+    editor: Option<EditorKind>,
 
     /// The fallback editor to use.
-    #[clap(long, default_value = "vim")]
+    #[clap(long, global = true, default_value = "vim")]
     fallback: String,
 }
 
 fn main() -> Result<()> {
     let args = Cli::try_parse()?;
-    let terminal = ratatui::init();
+
+    let path = match args.command {
+        Some(Commands::Edit { path }) => path,
+        Some(Commands::Rebase(rebase)) => return commands::rebase::run(rebase),
+        None => args
+            .path
+            .ok_or_else(|| color_eyre::eyre::eyre!("No path to edit"))?,
+    };
 
     let cwd = std::env::current_dir()?;
-    let path = if args.path.is_absolute() {
-        args.path
+    let path = if path.is_absolute() {
+        path
     } else {
-        cwd.join(args.path).canonicalize()?
+        cwd.join(path).canonicalize()?
     };
 
-    let result = if RebaseEditor::should_run(&path) {
-        let mut editor = RebaseEditor::new(path)?;
-        editor.run(terminal)
-    } else {
-        Command::new(args.fallback)
-            .arg(&path)
-            .status()
-            .map(|status| {
-                if status.success() {
-                    Ok(())
-                } else {
-                    Err(color_eyre::eyre::eyre!(
-                        "Vim exited with non-zero status: {}",
-                        status
-                    ))
-                }
-            })?
+    let editor = match args.editor {
+        Some(kind) => find_editor(kind),
+        None => detect_editor(&path),
+    };
+
+    let Some(editor) = editor else {
+        let status = Command::new(&args.fallback).arg(&path).status()?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(color_eyre::eyre::eyre!(
+                "{} exited with non-zero status: {}",
+                args.fallback,
+                status
+            ))
+        };
     };
 
+    let terminal = ratatui::init();
+    let result = (editor.launch)(path, terminal);
     ratatui::restore();
     result
 }